@@ -1,20 +1,266 @@
 use crate::structs::*;
 use crate::object::*;
+use crate::los::line_of_sight;
+use crate::maps::NEIGHBOURS;
+use crate::pathfinding::move_astar;
+use crate::ranged::fire;
+use crate::colors::*;
+use rand::Rng;
 
 const PLAYER: usize = 0;
 
-pub fn ai_take_turn(monster_id: usize, tcod: &Tcod, game: &mut Game, objects: &mut [Object]) {
+// how long a monster looks around after losing track of the player
+const SEARCH_TURNS: i32 = 5;
+// chance that a monster with nothing to do shuffles around this turn
+const WANDER_CHANCE: f32 = 0.5;
+// monsters hurt below this fraction of their HP run away for a while
+const FLEE_THRESHOLD: f32 = 0.3;
+const FLEE_TURNS: i32 = 8;
+// how far ranged monsters can spot the player, and how close they let them get
+const RANGED_SIGHT_RADIUS: f32 = 12.0;
+const RANGED_KEEP_AWAY: f32 = 3.0;
+
+pub fn ai_take_turn(monster_id: usize, game: &mut Game, objects: &mut [Object]) {
+    use Ai::*;
+    if let Some(ai) = objects[monster_id].ai.take() {
+        let new_ai = match ai {
+            Basic {
+                last_seen,
+                search_turns,
+            } => ai_basic(monster_id, last_seen, search_turns, game, objects),
+            Confused { previous_ai, turns } => ai_confused(monster_id, previous_ai, turns, game, objects),
+            Fleeing { previous_ai, turns } => ai_fleeing(monster_id, previous_ai, turns, game, objects),
+            Stationary => ai_stationary(monster_id, game, objects),
+            Ranged { last_seen } => ai_ranged(monster_id, last_seen, game, objects),
+        };
+        objects[monster_id].ai = Some(new_ai);
+    }
+}
+
+pub fn is_badly_hurt(monster: &Object) -> bool {
+    monster
+        .fighter
+        .is_some_and(|f| (f.hp as f32) < f.base_max_hp as f32 * FLEE_THRESHOLD)
+}
+
+/// a monster that was just hurt badly drops whatever it was doing and runs for it.
+/// Only the hit that takes it below the threshold does this, so once it gets its nerve
+/// back it fights on
+pub fn flee_if_badly_hurt(monster: &mut Object, was_badly_hurt: bool, game: &mut Game) {
+    let fights_on = !matches!(monster.ai, Some(Ai::Basic { .. }));
+    if was_badly_hurt || fights_on || !monster.alive || !is_badly_hurt(monster) {
+        return;
+    }
+    game.messages.add(format!("The {} turns to flee!", monster.name.to_lowercase()), LIGHT_YELLOW);
+    if let Some(ai) = monster.ai.take() {
+        monster.ai = Some(Ai::Fleeing {
+            previous_ai: Box::new(ai),
+            turns: FLEE_TURNS,
+        });
+    }
+}
+
+/// attack the player if they're alive and right next to the monster.
+/// Returns whether it attacked
+fn attack_if_adjacent(monster_id: usize, game: &mut Game, objects: &mut [Object]) -> bool {
+    if objects[monster_id].distance_to(&objects[PLAYER]) < 2.0
+        && objects[PLAYER].fighter.is_some_and(|f| f.hp > 0)
+    {
+        let (monster, player) = mut_two(monster_id, PLAYER, objects);
+        monster.attack(player, game);
+        true
+    } else {
+        false
+    }
+}
+
+fn ai_basic(
+    monster_id: usize,
+    last_seen: Option<(i32, i32)>,
+    search_turns: i32,
+    game: &mut Game,
+    objects: &mut [Object],
+) -> Ai {
     // a basic monster takes its turn. If you can see it, it can see you
     let (monster_x, monster_y) = objects[monster_id].pos();
-    if tcod.fov.is_in_fov(monster_x, monster_y) {
+    if game.fov.is_in_fov(monster_x, monster_y) {
+        let (player_x, player_y) = objects[PLAYER].pos();
         if objects[monster_id].distance_to(&objects[PLAYER]) >= 2.0 {
-            // move towards player if far away
-            let (player_x, player_y) = objects[PLAYER].pos();
-            move_towards(monster_id, player_x, player_y, &game.map, objects);
-        } else if objects[PLAYER].fighter.map_or(false, |f| f.hp > 0) {
+            // move towards player if far away, going around walls and other monsters
+            move_astar(monster_id, player_x, player_y, &game.map, objects);
+        } else {
             // close enough, attack! (if the player is still alive.)
-            let (monster, player) = mut_two(monster_id, PLAYER, objects);
-            monster.attack(player, game);
+            attack_if_adjacent(monster_id, game, objects);
+        }
+        return Ai::Basic {
+            last_seen: Some((player_x, player_y)),
+            search_turns: SEARCH_TURNS,
+        };
+    }
+
+    match last_seen {
+        // the player got away: go to where they were last seen
+        Some((x, y)) if (monster_x, monster_y) != (x, y) => {
+            move_astar(monster_id, x, y, &game.map, objects);
+            let moved = objects[monster_id].pos() != (monster_x, monster_y);
+            Ai::Basic {
+                // if the way there is blocked, give up and start searching
+                last_seen: if moved { Some((x, y)) } else { None },
+                search_turns,
+            }
+        }
+        // made it there or searching already: poke around nearby for a while
+        _ if search_turns > 0 => {
+            move_randomly(monster_id, game, objects);
+            Ai::Basic {
+                last_seen: None,
+                search_turns: search_turns - 1,
+            }
+        }
+        // nothing to go on, just wander
+        _ => {
+            if game.rng.gen::<f32>() < WANDER_CHANCE {
+                move_randomly(monster_id, game, objects);
+            }
+            Ai::basic()
+        }
+    }
+}
+
+fn ai_confused(
+    monster_id: usize,
+    previous_ai: Box<Ai>,
+    turns: i32,
+    game: &mut Game,
+    objects: &mut [Object],
+) -> Ai {
+    if turns >= 0 {
+        // still confused: stumble around (monsters bolted to the floor just flail)
+        if *previous_ai != Ai::Stationary {
+            move_randomly(monster_id, game, objects);
+        }
+        Ai::Confused {
+            previous_ai,
+            turns: turns - 1,
+        }
+    } else {
+        restore_ai(monster_id, *previous_ai, "is no longer confused!", game, objects)
+    }
+}
+
+/// a temporary AI wearing off: tell the player what the monster is back to and hand
+/// its old AI back
+fn restore_ai(monster_id: usize, previous_ai: Ai, news: &str, game: &mut Game, objects: &[Object]) -> Ai {
+    // restore the previous AI (this one will be deleted)
+    game.messages.add(
+        format!("The {} {}", objects[monster_id].name.to_lowercase(), news),
+        RED,
+    );
+    previous_ai
+}
+
+fn ai_fleeing(monster_id: usize, previous_ai: Box<Ai>, turns: i32, game: &mut Game, objects: &mut [Object]) -> Ai {
+    if turns <= 0 || !is_badly_hurt(&objects[monster_id]) {
+        return restore_ai(monster_id, *previous_ai, "regains its nerve!", game, objects);
+    }
+
+    // keep running, seen or not
+    if !step_away_from_player(monster_id, game, objects) {
+        // cornered, fight back
+        attack_if_adjacent(monster_id, game, objects);
+    }
+    Ai::Fleeing {
+        previous_ai,
+        turns: turns - 1,
+    }
+}
+
+fn ai_ranged(monster_id: usize, last_seen: Option<(i32, i32)>, game: &mut Game, objects: &mut [Object]) -> Ai {
+    let monster_pos = objects[monster_id].pos();
+    let player_pos = objects[PLAYER].pos();
+    let weapon = match objects[monster_id].ranged {
+        Some(weapon) => weapon,
+        None => return Ai::basic(), // nothing to shoot with, might as well charge
+    };
+
+    // it has to actually see the player to shoot, whatever the player can see
+    let distance = objects[monster_id].distance_to(&objects[PLAYER]);
+    if distance <= RANGED_SIGHT_RADIUS && line_of_sight(monster_pos, player_pos, &game.map) {
+        if distance < RANGED_KEEP_AWAY {
+            // too close for comfort: back off, or hit back if there's nowhere to go
+            if !step_away_from_player(monster_id, game, objects) {
+                attack_if_adjacent(monster_id, game, objects);
+            }
+        } else if distance <= weapon.range as f32 {
+            if objects[PLAYER].fighter.is_some_and(|f| f.hp > 0) {
+                let verb = objects[monster_id].attack_message.clone();
+                fire(monster_id, player_pos, weapon, &verb, game, objects);
+            }
+        } else {
+            // get in range
+            move_astar(monster_id, player_pos.0, player_pos.1, &game.map, objects);
+        }
+        return Ai::Ranged {
+            last_seen: Some(player_pos),
+        };
+    }
+
+    match last_seen {
+        // lost sight of the player: go to where they were seen last
+        Some((x, y)) if monster_pos != (x, y) => {
+            move_astar(monster_id, x, y, &game.map, objects);
+            let moved = objects[monster_id].pos() != monster_pos;
+            Ai::Ranged {
+                last_seen: if moved { Some((x, y)) } else { None },
+            }
         }
+        _ => {
+            if game.rng.gen::<f32>() < WANDER_CHANCE {
+                move_randomly(monster_id, game, objects);
+            }
+            Ai::ranged()
+        }
+    }
+}
+
+/// step to whichever free neighbouring tile gets furthest from the player.
+/// Returns false if there's no such tile
+fn step_away_from_player(monster_id: usize, game: &Game, objects: &mut [Object]) -> bool {
+    let (monster_x, monster_y) = objects[monster_id].pos();
+    let (player_x, player_y) = objects[PLAYER].pos();
+    let distance_from_player = |(x, y): (i32, i32)| (x - player_x).pow(2) + (y - player_y).pow(2);
+    let (mut best_x, mut best_y) = (monster_x, monster_y);
+    let mut best_distance = distance_from_player((monster_x, monster_y));
+    for &(dx, dy) in NEIGHBOURS.iter() {
+        let (x, y) = (monster_x + dx, monster_y + dy);
+        let distance = distance_from_player((x, y));
+        if distance > best_distance && !is_blocked(x, y, &game.map, objects) {
+            (best_x, best_y, best_distance) = (x, y, distance);
+        }
+    }
+    if (best_x, best_y) != (monster_x, monster_y) {
+        objects[monster_id].set_pos(best_x, best_y);
+        true
+    } else {
+        false
     }
 }
+
+fn ai_stationary(monster_id: usize, game: &mut Game, objects: &mut [Object]) -> Ai {
+    let (monster_x, monster_y) = objects[monster_id].pos();
+    if game.fov.is_in_fov(monster_x, monster_y) {
+        attack_if_adjacent(monster_id, game, objects);
+    }
+    Ai::Stationary
+}
+
+/// take a step in a random direction, if it's free
+fn move_randomly(monster_id: usize, game: &mut Game, objects: &mut [Object]) {
+    move_by(
+        monster_id,
+        game.rng.gen_range(-1, 2),
+        game.rng.gen_range(-1, 2),
+        &game.map,
+        objects,
+    );
+}
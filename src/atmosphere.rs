@@ -0,0 +1,96 @@
+use crate::maps::*;
+use crate::tile::TileKind;
+
+/// the compartment around (x, y) that air can escape from: flood fill outwards over
+/// every tile air can get through, i.e. anything that isn't blocked. Open doors,
+/// airlocks and corridors let it through, walls and closed doors don't
+fn compartment(map: &Map, x: i32, y: i32) -> Vec<(i32, i32)> {
+    let width = map.len() as i32;
+    let height = map.first().map_or(0, |column| column.len()) as i32;
+    let mut visited = vec![false; (width * height) as usize];
+    let mut tiles = vec![];
+    let mut frontier = vec![(x, y)];
+    while let Some((x, y)) = frontier.pop() {
+        if x < 0 || y < 0 || x >= width || y >= height || visited[(y * width + x) as usize] {
+            continue;
+        }
+        visited[(y * width + x) as usize] = true;
+        if map[x as usize][y as usize].blocked {
+            // walls and closed doors hold the air in
+            continue;
+        }
+        tiles.push((x, y));
+        frontier.extend(NEIGHBOURS.iter().map(|&(dx, dy)| (x + dx, y + dy)));
+    }
+    tiles
+}
+
+/// every tile connected to a hull breach
+fn breached(map: &Map) -> Vec<(i32, i32)> {
+    let breaches: Vec<(i32, i32)> = map
+        .iter()
+        .enumerate()
+        .flat_map(|(x, column)| {
+            column
+                .iter()
+                .enumerate()
+                .filter(|(_, tile)| tile.kind == TileKind::HullBreach)
+                .map(move |(y, _)| (x as i32, y as i32))
+        })
+        .collect();
+    breaches
+        .into_iter()
+        .flat_map(|(x, y)| compartment(map, x, y))
+        .collect()
+}
+
+/// let the air out of the compartment around (x, y) and mark it as vacuum.
+/// Returns whether there was any air to let out
+pub fn vent(map: &mut Map, x: i32, y: i32) -> bool {
+    let mut vented = false;
+    for (x, y) in compartment(map, x, y) {
+        let tile = &mut map[x as usize][y as usize];
+        vented |= tile.pressurized;
+        tile.pressurized = false;
+    }
+    vented
+}
+
+/// vent everything connected to a hull breach. Run it again whenever a new way
+/// opens up for the air to escape. Returns whether any air got out
+pub fn vent_breaches(map: &mut Map) -> bool {
+    let mut vented = false;
+    for (x, y) in breached(map) {
+        let tile = &mut map[x as usize][y as usize];
+        vented |= tile.pressurized;
+        tile.pressurized = false;
+    }
+    vented
+}
+
+/// life support fills everything that's no longer connected to a breach back up with
+/// air. Run it whenever a way for the air to escape gets shut. Returns whether any
+/// air came back
+pub fn repressurize(map: &mut Map) -> bool {
+    let width = map.len();
+    let height = map.first().map_or(0, |column| column.len());
+    let mut leaking = vec![vec![false; height]; width];
+    for (x, y) in breached(map) {
+        leaking[x as usize][y as usize] = true;
+    }
+    let mut refilled = false;
+    for (x, column) in map.iter_mut().enumerate() {
+        for (y, tile) in column.iter_mut().enumerate() {
+            if !tile.blocked && !tile.pressurized && !leaking[x][y] {
+                tile.pressurized = true;
+                refilled = true;
+            }
+        }
+    }
+    refilled
+}
+
+/// whether there's air to breathe at (x, y)
+pub fn is_pressurized(map: &Map, x: i32, y: i32) -> bool {
+    map[x as usize][y as usize].pressurized
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// an RGB color. Same layout as `tcod::colors::Color`, so the core doesn't need libtcod
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b }
+    }
+}
+
+#[cfg(feature = "tcod")]
+impl From<Color> for tcod::colors::Color {
+    fn from(color: Color) -> Self {
+        tcod::colors::Color::new(color.r, color.g, color.b)
+    }
+}
+
+// the libtcod palette entries the game uses
+pub const BLACK: Color = Color { r: 0, g: 0, b: 0 };
+pub const WHITE: Color = Color { r: 255, g: 255, b: 255 };
+pub const LIGHT_GREY: Color = Color { r: 159, g: 159, b: 159 };
+pub const RED: Color = Color { r: 255, g: 0, b: 0 };
+pub const DARK_RED: Color = Color { r: 191, g: 0, b: 0 };
+pub const DARKER_RED: Color = Color { r: 127, g: 0, b: 0 };
+pub const LIGHT_RED: Color = Color { r: 255, g: 63, b: 63 };
+pub const ORANGE: Color = Color { r: 255, g: 127, b: 0 };
+pub const LIGHT_YELLOW: Color = Color { r: 255, g: 255, b: 63 };
+pub const YELLOW: Color = Color { r: 255, g: 255, b: 0 };
+pub const GREEN: Color = Color { r: 0, g: 255, b: 0 };
+pub const LIGHT_GREEN: Color = Color { r: 63, g: 255, b: 63 };
+pub const DESATURATED_GREEN: Color = Color { r: 63, g: 127, b: 63 };
+pub const VIOLET: Color = Color { r: 127, g: 0, b: 255 };
+pub const LIGHT_BLUE: Color = Color { r: 63, g: 63, b: 255 };
+pub const AZURE: Color = Color { r: 0, g: 127, b: 255 };
+pub const SKY: Color = Color { r: 0, g: 191, b: 255 };
+pub const BRASS: Color = Color { r: 191, g: 151, b: 96 };
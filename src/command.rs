@@ -0,0 +1,115 @@
+use crate::colors::*;
+use crate::doors::close_door_near_player;
+use crate::effects::{has_effect, EffectKind};
+use crate::game::take_stairs;
+use crate::items::*;
+use crate::object::*;
+use crate::ranged::player_fire;
+use crate::structs::*;
+use crate::tile::TileKind;
+use serde::{Deserialize, Serialize};
+
+const PLAYER: usize = 0;
+
+/// something the player does. The frontend turns keys into these, but they could just
+/// as well come from a replay, a bot or a test
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Command {
+    /// step one tile, attacking whatever is there and opening closed doors
+    Move { dx: i32, dy: i32 },
+    /// attack whatever is one tile away, without moving
+    Attack { dx: i32, dy: i32 },
+    /// let a turn go by
+    Wait,
+    PickUp,
+    /// use the item in this inventory slot
+    UseItem(usize),
+    /// drop the item in this inventory slot
+    DropItem(usize),
+    /// fire the equipped gun at this tile
+    Fire { x: i32, y: i32 },
+    CloseDoor,
+    Descend,
+    Ascend,
+}
+
+/// carry out a command for the player. Returns whether it took a turn, in which
+/// case the monsters get theirs
+pub fn apply_command(command: Command, game: &mut Game, objects: &mut Vec<Object>) -> bool {
+    use Command::*;
+
+    // the dead don't get to do anything, and the monsters don't get a turn out of it either
+    if !objects[PLAYER].alive {
+        return false;
+    }
+
+    // a step is one tile in one of the eight directions, nothing else
+    if let Move { dx, dy } | Attack { dx, dy } = command {
+        if !is_step(dx, dy) {
+            game.messages.add("You can't reach that far.", WHITE);
+            return false;
+        }
+    }
+
+    // stunned: whatever the player tries, the turn goes by
+    if has_effect(&objects[PLAYER], EffectKind::Stunned) {
+        game.messages.add("You are too stunned to do anything!", LIGHT_YELLOW);
+        return true;
+    }
+
+    match command {
+        Move { dx, dy } => {
+            player_move_or_attack(dx, dy, game, objects);
+            true
+        }
+        Attack { dx, dy } => {
+            let target = (objects[PLAYER].x + dx, objects[PLAYER].y + dy);
+            let target_id = objects
+                .iter()
+                .position(|object| object.fighter.is_some() && object.alive && object.pos() == target);
+            match target_id {
+                Some(target_id) if target_id != PLAYER => {
+                    let (player, target) = mut_two(PLAYER, target_id, objects);
+                    player.attack(target, game);
+                    true
+                }
+                _ => {
+                    game.messages.add("There is nothing there to attack.", WHITE);
+                    false
+                }
+            }
+        }
+        Wait => true,
+        PickUp => pick_up_at_player(game, objects),
+        UseItem(inventory_id) if inventory_id < game.inventory.len() => use_item(inventory_id, game, objects),
+        DropItem(inventory_id) if inventory_id < game.inventory.len() => {
+            drop_item(inventory_id, game, objects);
+            true
+        }
+        UseItem(_) | DropItem(_) => {
+            game.messages.add("You don't have that.", WHITE);
+            false
+        }
+        Fire { x, y } => player_fire((x, y), game, objects),
+        CloseDoor => close_door_near_player(game, objects),
+        Descend | Ascend => {
+            let (x, y) = objects[PLAYER].pos();
+            let (stairs, message) = if command == Descend {
+                (TileKind::StairsDown, "There are no stairs down here.")
+            } else {
+                (TileKind::StairsUp, "There are no stairs up here.")
+            };
+            if game.map[x as usize][y as usize].kind == stairs {
+                take_stairs(game, objects)
+            } else {
+                game.messages.add(message, WHITE);
+                false
+            }
+        }
+    }
+}
+
+/// whether (dx, dy) goes to one of the tiles next to where it starts
+fn is_step(dx: i32, dy: i32) -> bool {
+    (-1..=1).contains(&dx) && (-1..=1).contains(&dy) && (dx, dy) != (0, 0)
+}
@@ -0,0 +1,108 @@
+use crate::atmosphere::{repressurize, vent_breaches};
+use crate::colors::*;
+use crate::game::update_fov_tile;
+use crate::items::Item;
+use crate::maps::NEIGHBOURS;
+use crate::object::*;
+use crate::structs::*;
+use crate::tile::TileKind;
+
+const PLAYER: usize = 0;
+
+/// whether the player is carrying something to swipe at locked doors
+pub fn has_keycard(game: &Game) -> bool {
+    game.inventory.iter().any(|item| item.item == Some(Item::Keycard))
+}
+
+/// the player tries to open the door at (x, y), swiping a keycard if it's locked.
+/// Opening a door onto vacuum lets the air out of the compartment behind it
+pub fn open_door(x: i32, y: i32, game: &mut Game) {
+    let tile = game.map[x as usize][y as usize];
+    if !tile.is_door() || !tile.blocked {
+        return;
+    }
+    if tile.kind == TileKind::LockedDoor {
+        if !has_keycard(game) {
+            game.messages.add("The door is locked. It needs a keycard.", RED);
+            return;
+        }
+        game.messages.add("You swipe your keycard and the lock clicks open.", LIGHT_GREEN);
+        game.map[x as usize][y as usize].kind = TileKind::Door;
+    }
+
+    game.map[x as usize][y as usize].set_open(true);
+    update_fov_tile(game, x, y);
+    let message = match tile.kind {
+        TileKind::Airlock => "The airlock cycles and slides open.",
+        _ => "You open the door.",
+    };
+    game.messages.add(message, WHITE);
+    if vent_breaches(&mut game.map) {
+        game.messages.add("Air rushes out through the doorway!", ORANGE);
+    }
+}
+
+/// close the door at (x, y), unless someone is standing in it. Whatever that seals
+/// off from the breaches gets its air back. Returns whether it closed
+pub fn close_door(x: i32, y: i32, game: &mut Game, objects: &[Object]) -> bool {
+    let tile = game.map[x as usize][y as usize];
+    if !tile.is_door() || tile.blocked {
+        return false;
+    }
+    if objects.iter().any(|object| object.pos() == (x, y)) {
+        game.messages.add("Something is in the way.", RED);
+        return false;
+    }
+    game.map[x as usize][y as usize].set_open(false);
+    update_fov_tile(game, x, y);
+    game.messages.add("You close the door.", WHITE);
+    if repressurize(&mut game.map) {
+        game.messages.add("Vents hiss as the air comes back.", LIGHT_BLUE);
+    }
+    true
+}
+
+/// airlocks cycle shut on their own once nobody is in one or next to it. Whatever that
+/// seals off from the breaches gets its air back
+pub fn cycle_airlocks(game: &mut Game, objects: &[Object]) {
+    let mut cycled = false;
+    for x in 0..game.map.len() {
+        for y in 0..game.map[x].len() {
+            let tile = game.map[x][y];
+            if tile.kind != TileKind::Airlock || tile.blocked {
+                continue;
+            }
+            let (x, y) = (x as i32, y as i32);
+            let nearby = objects.iter().any(|object| {
+                object.pos() == (x, y)
+                    || (object.alive && (object.x - x).abs() <= 1 && (object.y - y).abs() <= 1)
+            });
+            if nearby {
+                continue;
+            }
+            game.map[x as usize][y as usize].set_open(false);
+            update_fov_tile(game, x, y);
+            if game.fov.is_in_fov(x, y) {
+                game.messages.add("The airlock cycles shut.", WHITE);
+            }
+            cycled = true;
+        }
+    }
+    if cycled && repressurize(&mut game.map) {
+        game.messages.add("Vents hiss as the air comes back.", LIGHT_BLUE);
+    }
+}
+
+/// close an open door next to the player, if there is one. Returns whether that took a turn
+pub fn close_door_near_player(game: &mut Game, objects: &[Object]) -> bool {
+    let (player_x, player_y) = objects[PLAYER].pos();
+    for &(dx, dy) in NEIGHBOURS.iter() {
+        let (x, y) = (player_x + dx, player_y + dy);
+        let tile = game.map[x as usize][y as usize];
+        if tile.is_door() && !tile.blocked {
+            return close_door(x, y, game, objects);
+        }
+    }
+    game.messages.add("There is no open door next to you.", WHITE);
+    false
+}
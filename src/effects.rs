@@ -0,0 +1,195 @@
+use crate::colors::*;
+use crate::object::*;
+use crate::structs::*;
+use serde::{Deserialize, Serialize};
+use std::cmp;
+use std::mem;
+
+const PLAYER: usize = 0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EffectKind {
+    Bleeding,
+    Poisoned,
+    Stunned,
+    Suffocating,
+    Burning,
+    Regenerating,
+}
+
+impl EffectKind {
+    pub fn name(self) -> &'static str {
+        use EffectKind::*;
+        match self {
+            Bleeding => "bleeding",
+            Poisoned => "poisoned",
+            Stunned => "stunned",
+            Suffocating => "suffocating",
+            Burning => "burning",
+            Regenerating => "regenerating",
+        }
+    }
+
+    /// a short tag to show in the side panel
+    pub fn tag(self) -> &'static str {
+        use EffectKind::*;
+        match self {
+            Bleeding => "BLD",
+            Poisoned => "PSN",
+            Stunned => "STN",
+            Suffocating => "AIR",
+            Burning => "BRN",
+            Regenerating => "REG",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        use EffectKind::*;
+        match self {
+            Bleeding => LIGHT_RED,
+            Poisoned => LIGHT_GREEN,
+            Stunned => LIGHT_YELLOW,
+            Suffocating => SKY,
+            Burning => ORANGE,
+            Regenerating => GREEN,
+        }
+    }
+
+    /// what goes on the death screen if this is what finished the player off
+    fn cause_of_death(self) -> &'static str {
+        use EffectKind::*;
+        match self {
+            Bleeding => "bled to death",
+            Poisoned => "succumbed to poison",
+            Suffocating => "suffocated",
+            Burning => "burned to death",
+            Stunned | Regenerating => "died",
+        }
+    }
+}
+
+/// a temporary condition on an object, lasting `turns` more turns. `potency` is the
+/// damage (or healing) it does every turn. Whatever dies of one the player put on it
+/// counts as the player's kill
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub kind: EffectKind,
+    pub turns: i32,
+    pub potency: i32,
+    #[serde(default)]
+    pub by_player: bool,
+}
+
+impl StatusEffect {
+    pub fn new(kind: EffectKind, turns: i32, potency: i32) -> Self {
+        StatusEffect {
+            kind,
+            turns,
+            potency,
+            by_player: false,
+        }
+    }
+}
+
+/// whether the object is currently under the given effect
+pub fn has_effect(object: &Object, kind: EffectKind) -> bool {
+    object.effects.iter().any(|e| e.kind == kind)
+}
+
+/// tell the player about something happening to the object, if they can see it
+fn report(object: &Object, text: &str, color: Color, game: &mut Game) {
+    if object.is_player() {
+        game.messages.add(format!("You are {}", text), color);
+    } else if game.fov.is_in_fov(object.x, object.y) {
+        game.messages.add(format!("The {} is {}", object.name.to_lowercase(), text), color);
+    }
+}
+
+/// put a status effect on the object. Getting something it already has stacks onto
+/// the old one instead: wounds bleed harder, poison lasts longer, and everything
+/// else keeps whichever is the longer and the stronger of the two
+pub fn apply_effect(object: &mut Object, effect: StatusEffect, game: &mut Game) {
+    if !object.alive {
+        return;
+    }
+    match object.effects.iter_mut().find(|e| e.kind == effect.kind) {
+        Some(existing) => {
+            existing.by_player |= effect.by_player;
+            match effect.kind {
+                EffectKind::Bleeding => {
+                    existing.potency += effect.potency;
+                    existing.turns = cmp::max(existing.turns, effect.turns);
+                }
+                EffectKind::Poisoned => {
+                    existing.turns += effect.turns;
+                    existing.potency = cmp::max(existing.potency, effect.potency);
+                }
+                _ => {
+                    existing.turns = cmp::max(existing.turns, effect.turns);
+                    existing.potency = cmp::max(existing.potency, effect.potency);
+                }
+            }
+        }
+        None => {
+            object.effects.push(effect);
+            report(object, &format!("{}!", effect.kind.name()), effect.kind.color(), game);
+        }
+    }
+}
+
+/// run everyone's status effects for a turn: they do their damage or healing,
+/// then count down, and the ones that run out wear off
+pub fn tick_effects(game: &mut Game, objects: &mut [Object]) {
+    // what the player's effects killed, to score once everyone's been through
+    let mut player_kills = vec![];
+    for object in objects.iter_mut() {
+        if object.effects.is_empty() {
+            continue;
+        }
+        if !object.alive {
+            // the dead don't bleed
+            object.effects.clear();
+            continue;
+        }
+
+        let mut effects = mem::take(&mut object.effects);
+        for effect in effects.iter_mut() {
+            match effect.kind {
+                EffectKind::Bleeding
+                | EffectKind::Poisoned
+                | EffectKind::Suffocating
+                | EffectKind::Burning => {
+                    if let Some(victim) = object.take_damage(effect.potency, game) {
+                        if victim.on_death == DeathCallback::Player {
+                            if game.cause_of_death.is_none() {
+                                game.cause_of_death = Some(effect.kind.cause_of_death().into());
+                            }
+                        } else if effect.by_player {
+                            player_kills.push(victim);
+                        }
+                    }
+                }
+                EffectKind::Regenerating => object.heal(effect.potency, game),
+                // stunned objects just lose their turns
+                EffectKind::Stunned => {}
+            }
+            effect.turns -= 1;
+            if !object.alive {
+                break;
+            }
+        }
+
+        if !object.alive {
+            continue;
+        }
+        for effect in effects.iter().filter(|e| e.turns <= 0) {
+            report(object, &format!("no longer {}.", effect.kind.name()), LIGHT_GREY, game);
+        }
+        effects.retain(|e| e.turns > 0);
+        object.effects = effects;
+    }
+
+    for victim in player_kills {
+        objects[PLAYER].record_kill(victim, game);
+    }
+}
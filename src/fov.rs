@@ -0,0 +1,146 @@
+/// Field of view over the map, computed with recursive shadowcasting.
+/// Mirrors the parts of `tcod::map::Map` the game used, but works without a display.
+#[derive(Clone, Debug, Default)]
+pub struct FovMap {
+    width: i32,
+    height: i32,
+    transparent: Vec<bool>,
+    walkable: Vec<bool>,
+    in_fov: Vec<bool>,
+}
+
+// transforms from the first octant to each of the eight octants
+const OCTANTS: [(i32, i32, i32, i32); 8] = [
+    (1, 0, 0, 1),
+    (0, 1, 1, 0),
+    (0, -1, 1, 0),
+    (-1, 0, 0, 1),
+    (-1, 0, 0, -1),
+    (0, -1, -1, 0),
+    (0, 1, -1, 0),
+    (1, 0, 0, -1),
+];
+
+impl FovMap {
+    pub fn new(width: i32, height: i32) -> Self {
+        let size = (width * height) as usize;
+        FovMap {
+            width,
+            height,
+            transparent: vec![false; size],
+            walkable: vec![false; size],
+            in_fov: vec![false; size],
+        }
+    }
+
+    pub fn size(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            None
+        } else {
+            Some((y * self.width + x) as usize)
+        }
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, transparent: bool, walkable: bool) {
+        if let Some(i) = self.index(x, y) {
+            self.transparent[i] = transparent;
+            self.walkable[i] = walkable;
+        }
+    }
+
+    pub fn is_transparent(&self, x: i32, y: i32) -> bool {
+        self.index(x, y).is_some_and(|i| self.transparent[i])
+    }
+
+    pub fn is_walkable(&self, x: i32, y: i32) -> bool {
+        self.index(x, y).is_some_and(|i| self.walkable[i])
+    }
+
+    pub fn is_in_fov(&self, x: i32, y: i32) -> bool {
+        self.index(x, y).is_some_and(|i| self.in_fov[i])
+    }
+
+    /// recompute what is visible from the origin. A radius of 0 means unlimited,
+    /// same as libtcod
+    pub fn compute_fov(&mut self, origin_x: i32, origin_y: i32, radius: i32, light_walls: bool) {
+        for cell in self.in_fov.iter_mut() {
+            *cell = false;
+        }
+        let origin = match self.index(origin_x, origin_y) {
+            Some(i) => i,
+            None => return,
+        };
+        self.in_fov[origin] = true;
+
+        let radius = if radius <= 0 {
+            self.width.max(self.height)
+        } else {
+            radius
+        };
+        for &octant in OCTANTS.iter() {
+            self.cast_light((origin_x, origin_y), 1, 1.0, 0.0, radius, octant, light_walls);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn cast_light(
+        &mut self,
+        origin: (i32, i32),
+        row: i32,
+        mut start: f32,
+        end: f32,
+        radius: i32,
+        (xx, xy, yx, yy): (i32, i32, i32, i32),
+        light_walls: bool,
+    ) {
+        if start < end {
+            return;
+        }
+        let radius_squared = radius * radius;
+        let mut new_start = 0.0;
+        for j in row..=radius {
+            let dy = -j;
+            let mut blocked = false;
+            for dx in -j..=0 {
+                let x = origin.0 + dx * xx + dy * xy;
+                let y = origin.1 + dx * yx + dy * yy;
+                let left_slope = (dx as f32 - 0.5) / (dy as f32 + 0.5);
+                let right_slope = (dx as f32 + 0.5) / (dy as f32 - 0.5);
+                if start < right_slope {
+                    continue;
+                } else if end > left_slope {
+                    break;
+                }
+
+                // cells off the map block everything behind them
+                let opaque = !self.is_transparent(x, y);
+                if dx * dx + dy * dy <= radius_squared && (light_walls || !opaque) {
+                    if let Some(i) = self.index(x, y) {
+                        self.in_fov[i] = true;
+                    }
+                }
+
+                if blocked {
+                    if opaque {
+                        new_start = right_slope;
+                        continue;
+                    }
+                    blocked = false;
+                    start = new_start;
+                } else if opaque && j < radius {
+                    // this is a blocking cell, scan the next row up to it
+                    blocked = true;
+                    self.cast_light(origin, j + 1, start, left_slope, radius, (xx, xy, yx, yy), light_walls);
+                    new_start = right_slope;
+                }
+            }
+            if blocked {
+                break;
+            }
+        }
+    }
+}
@@ -1,21 +1,51 @@
-use tcod::input::Key;
+use tcod::colors::*;
+use tcod::console::*;
+use tcod::input::{self, Event, Key};
 use tcod::input::KeyCode::*;
-use crate::structs::*;
-use crate::object::*;
+use aeros::structs::*;
+use aeros::object::*;
+use aeros::command::Command;
+use aeros::doors::has_keycard;
+use aeros::game::monster_in_view;
+use aeros::items::closest_monster;
+use aeros::keybindings::KeyCommand;
+use aeros::los::line;
+use aeros::maps::{MAP_HEIGHT, MAP_WIDTH};
+use aeros::pathfinding::travel_path;
+use aeros::ranged::*;
+use crate::menus::{character_screen, inventory_menu};
+use crate::{render_all, Tcod};
 use PlayerAction::*;
 
 const PLAYER: usize = 0;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum PlayerAction {
-    TookTurn,
+    /// something for the game to do with `apply_command`
+    Act(Command),
     DidntTakeTurn,
     Exit,
 }
 
-pub fn handle_input(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>) -> PlayerAction {
-    let key = tcod.root.wait_for_keypress(true);
+/// check for a key or the mouse and turn it into what the player wants to do. Menus and
+/// aiming happen here, the game itself is left alone until the command is applied.
+/// With nothing pressed, the player keeps travelling wherever they last clicked
+pub fn handle_input(tcod: &mut Tcod, game: &mut Game, objects: &[Object]) -> PlayerAction {
     let player_alive = objects[PLAYER].alive;
+    let key = match input::check_for_event(input::KEY_PRESS | input::MOUSE) {
+        Some((_, Event::Key(key))) => key,
+        Some((_, Event::Mouse(mouse))) => {
+            tcod.mouse = mouse;
+            if mouse.lbutton_pressed && player_alive {
+                start_travel((mouse.cx as i32, mouse.cy as i32), tcod, game, objects);
+            }
+            return DidntTakeTurn;
+        }
+        _ => return travel_step(tcod, game, objects),
+    };
+    // pressing anything stops the travel
+    tcod.travel.clear();
+
     match (key, key.text(), player_alive) {
         (
             Key {
@@ -32,26 +62,177 @@ pub fn handle_input(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>)
             DidntTakeTurn
         }
 
-        (Key { code: Escape, .. }, _, _) => return Exit, // exit game
-
-        // movement keys
-        (Key { code: Up, .. }, _, true) => {
-            player_move_or_attack(0, -1, game, objects);
-            return TookTurn;
-        },
-        (Key { code: Down, .. }, _, true) => {
-            player_move_or_attack(0, 1, game, objects);
-            return TookTurn;
-        },
-        (Key { code: Left, .. }, _, true) => {
-            player_move_or_attack(-1, 0, game, objects);
-            return TookTurn;
-        },
-        (Key { code: Right, .. }, _, true) => {
-            player_move_or_attack(1, 0, game, objects);
-            return TookTurn;
-        },
-
-        _ => return DidntTakeTurn,
+        (Key { code: Escape, .. }, _, _) => Exit, // save and go back to the main menu
+
+        (_, _, true) => {
+            let command = tcod.keys.command(&key_name(key));
+            // move, or attack whatever is in the way
+            if let Some((dx, dy)) = command.and_then(KeyCommand::direction) {
+                return Act(Command::Move { dx, dy });
+            }
+            match command {
+                Some(KeyCommand::Wait) => Act(Command::Wait),
+                Some(KeyCommand::Descend) => Act(Command::Descend),
+                Some(KeyCommand::Ascend) => Act(Command::Ascend),
+                Some(KeyCommand::PickUp) => Act(Command::PickUp),
+                Some(KeyCommand::CloseDoor) => Act(Command::CloseDoor),
+
+                // show the inventory: if an item is selected, use it
+                Some(KeyCommand::Inventory) => {
+                    let inventory_index = inventory_menu(
+                        &game.inventory,
+                        "Press the key next to an item to use it, or any other to cancel.\n",
+                        &mut tcod.root,
+                    );
+                    inventory_index.map_or(DidntTakeTurn, |index| Act(Command::UseItem(index)))
+                }
+
+                // show the inventory: if an item is selected, drop it
+                Some(KeyCommand::Drop) => {
+                    let inventory_index = inventory_menu(
+                        &game.inventory,
+                        "Press the key next to an item to drop it, or any other to cancel.\n",
+                        &mut tcod.root,
+                    );
+                    inventory_index.map_or(DidntTakeTurn, |index| Act(Command::DropItem(index)))
+                }
+
+                // aim the gun in hand
+                Some(KeyCommand::Fire) => {
+                    let weapon = equipped_ranged_weapon(game).and_then(|id| game.inventory[id].ranged);
+                    match weapon {
+                        Some(weapon) => match target_tile(tcod, game, objects, weapon.range) {
+                            Some((x, y)) => Act(Command::Fire { x, y }),
+                            None => DidntTakeTurn,
+                        },
+                        None => {
+                            game.messages.add("You have nothing to shoot with.", aeros::colors::WHITE);
+                            DidntTakeTurn
+                        }
+                    }
+                }
+
+                // show the character information
+                Some(KeyCommand::CharacterScreen) => {
+                    character_screen(game, &objects[PLAYER], &mut tcod.root);
+                    DidntTakeTurn
+                }
+
+                _ => DidntTakeTurn,
+            }
+        }
+
+        _ => DidntTakeTurn,
+    }
+}
+
+/// head for the clicked tile, if the player knows a way there
+fn start_travel(target: (i32, i32), tcod: &mut Tcod, game: &mut Game, objects: &[Object]) {
+    let (x, y) = target;
+    let on_map = x >= 0 && y >= 0 && x < MAP_WIDTH && y < MAP_HEIGHT;
+    if !on_map || !game.map[x as usize][y as usize].explored || target == objects[PLAYER].pos() {
+        return;
+    }
+    if monster_in_view(game, objects) {
+        game.messages.add("Not with a monster in view!", aeros::colors::WHITE);
+        return;
+    }
+    match travel_path(objects[PLAYER].pos(), target, &game.map, has_keycard(game)) {
+        Some(path) => tcod.travel = path,
+        None => game.messages.add("You don't know a way there.", aeros::colors::WHITE),
+    }
+}
+
+/// the next step of the travel, unless a monster has come into view or the last
+/// step didn't go as planned
+fn travel_step(tcod: &mut Tcod, game: &mut Game, objects: &[Object]) -> PlayerAction {
+    if tcod.travel.is_empty() {
+        return DidntTakeTurn;
+    }
+    if !objects[PLAYER].alive || monster_in_view(game, objects) {
+        tcod.travel.clear();
+        game.messages.add("You stop, there's something there.", aeros::colors::LIGHT_YELLOW);
+        return DidntTakeTurn;
+    }
+    let (x, y) = tcod.travel[0];
+    let (dx, dy) = (x - objects[PLAYER].x, y - objects[PLAYER].y);
+    if dx.abs() > 1 || dy.abs() > 1 {
+        // knocked off the path, e.g. by a stun
+        tcod.travel.clear();
+        return DidntTakeTurn;
+    }
+    // walking into a closed door only opens it, the step is still to be taken
+    let tile = game.map[x as usize][y as usize];
+    if !(tile.is_door() && tile.blocked) {
+        tcod.travel.remove(0);
+    }
+    Act(Command::Move { dx, dy })
+}
+
+/// the name a key goes by in the key bindings: the character it types, or its key code
+fn key_name(key: Key) -> String {
+    match key.code {
+        Text => key.text().to_string(),
+        code => format!("{:?}", code),
+    }
+}
+
+/// let the player pick a visible tile within `max_range` with the movement keys or the mouse.
+/// Enter, `f` or a left click picks the tile under the cursor; Escape or a right
+/// click cancels and returns `None`
+pub fn target_tile(tcod: &mut Tcod, game: &Game, objects: &[Object], max_range: i32) -> Option<(i32, i32)> {
+    let player_pos = objects[PLAYER].pos();
+    // start on the closest monster, if there is one
+    let mut cursor = closest_monster(max_range, game, objects).map_or(player_pos, |id| objects[id].pos());
+
+    while !tcod.root.window_closed() {
+        let (x, y) = cursor;
+        let distance = (((x - player_pos.0).pow(2) + (y - player_pos.1).pow(2)) as f32).sqrt();
+        let valid = cursor != player_pos && game.fov.is_in_fov(x, y) && distance <= max_range as f32;
+
+        // draw the map with the line of fire and the cursor on top
+        tcod.con.clear();
+        render_all(tcod, game, objects);
+        for (line_x, line_y) in line(player_pos, cursor) {
+            tcod.root.set_char_background(line_x, line_y, DARKER_YELLOW, BackgroundFlag::Set);
+        }
+        tcod.root
+            .set_char_background(x, y, if valid { LIGHT_GREEN } else { LIGHT_RED }, BackgroundFlag::Set);
+        tcod.root.set_default_foreground(WHITE);
+        tcod.root.print_ex(
+            0,
+            0,
+            BackgroundFlag::None,
+            TextAlignment::Left,
+            "Aim with the movement keys or mouse, fire with Enter/f/click, Esc to cancel",
+        );
+        tcod.root.flush();
+
+        let (dx, dy) = match input::check_for_event(input::KEY_PRESS | input::MOUSE) {
+            Some((_, Event::Mouse(mouse))) => {
+                cursor = (mouse.cx as i32, mouse.cy as i32);
+                if mouse.lbutton_pressed && valid {
+                    return Some(cursor);
+                } else if mouse.rbutton_pressed {
+                    return None;
+                }
+                (0, 0)
+            }
+            Some((_, Event::Key(key))) => match (key.code, key.text()) {
+                (Escape, _) => return None,
+                (Enter, _) | (Text, "f") if valid => return Some(cursor),
+                _ => tcod
+                    .keys
+                    .command(&key_name(key))
+                    .and_then(KeyCommand::direction)
+                    .unwrap_or((0, 0)),
+            },
+            None => (0, 0),
+        };
+        cursor = (
+            (cursor.0 + dx).clamp(0, MAP_WIDTH - 1),
+            (cursor.1 + dy).clamp(0, MAP_HEIGHT - 1),
+        );
     }
+    None
 }
@@ -0,0 +1,257 @@
+use crate::ai::*;
+use crate::colors::*;
+use crate::doors::cycle_airlocks;
+use crate::effects::*;
+use crate::items::Item;
+use crate::fov::FovMap;
+use crate::mapgen::Generator;
+use crate::maps::*;
+use crate::object::*;
+use crate::rng::GameRng;
+use crate::structs::*;
+use crate::templates::Templates;
+use crate::survival::*;
+use crate::tile::TileKind;
+use std::collections::BTreeMap;
+use std::mem;
+
+// FOV
+pub const FOV_LIGHT_WALLS: bool = true; // light walls or not
+pub const LANTERN_RADIUS: i32 = 50;
+
+// experience and level-ups
+const LEVEL_UP_BASE: i32 = 200;
+const LEVEL_UP_FACTOR: i32 = 150;
+
+const PLAYER: usize = 0;
+
+/// create the player, generate the map from `seed`, filling it from `templates`, and get
+/// everything ready for the first turn
+pub fn new_game(seed: u64, templates: Templates) -> (Game, Vec<Object>) {
+    let mut player = Object::new(25, 23, '@', "You", DESATURATED_GREEN, false);
+    player.fighter = Some(Fighter {
+        base_max_hp: 30,
+        hp: 30,
+        base_defense: 2,
+        base_power: 5,
+        xp: 0,
+        on_death: DeathCallback::Player,
+    });
+    player.alive = true;
+    player.attack_message = "attack".into();
+
+    let mut objects = vec![player];
+    let mut rng = GameRng::new(seed);
+    let generator = Generator::for_level(1, &mut rng);
+    let mut game = Game {
+        map: make_map(MAP_HEIGHT, MAP_WIDTH, &mut objects, &mut rng, &templates, generator, 1),
+        messages: Messages::new(),
+        fov: FovMap::new(MAP_WIDTH, MAP_HEIGHT),
+        rng,
+        seed,
+        turn: 0,
+        level: 1,
+        levels: BTreeMap::new(),
+        kills: 0,
+        cause_of_death: None,
+        oxygen: MAX_OXYGEN,
+        inventory: vec![],
+        templates,
+    };
+
+    // everyone starts out suited up
+    if let Some(template) = game.templates.item(Item::Hardsuit) {
+        let mut hardsuit = template.spawn(0, 0);
+        if let Some(ref mut equipment) = hardsuit.equipment {
+            equipment.equipped = true;
+        }
+        game.inventory.push(hardsuit);
+    }
+    let max_hp = objects[PLAYER].max_hp(&game);
+    if let Some(ref mut fighter) = objects[PLAYER].fighter {
+        fighter.hp = max_hp;
+    }
+
+    game.messages.add(
+        "Welcome to space. Wear your hardsuit and prepare some medicine.",
+        RED,
+    );
+    game.messages.add(format!("Dungeon seed: {}", seed), WHITE);
+
+    initialise_fov(&mut game);
+    compute_fov(&mut game, &objects);
+
+    (game, objects)
+}
+
+/// copy the map's sight and movement blockers into the FOV map
+pub fn initialise_fov(game: &mut Game) {
+    for y in 0..MAP_HEIGHT {
+        for x in 0..MAP_WIDTH {
+            game.fov.set(
+                x,
+                y,
+                !game.map[x as usize][y as usize].block_sight,
+                !game.map[x as usize][y as usize].blocked,
+            );
+        }
+    }
+}
+
+/// copy a single tile into the FOV map after it changed, e.g. a door opening
+pub fn update_fov_tile(game: &mut Game, x: i32, y: i32) {
+    let tile = game.map[x as usize][y as usize];
+    game.fov.set(x, y, !tile.block_sight, !tile.blocked);
+}
+
+/// recompute the FOV around the player and mark everything in it as explored
+pub fn compute_fov(game: &mut Game, objects: &[Object]) {
+    let player = &objects[PLAYER];
+    game.fov
+        .compute_fov(player.x, player.y, LANTERN_RADIUS, FOV_LIGHT_WALLS);
+
+    for y in 0..MAP_HEIGHT {
+        for x in 0..MAP_WIDTH {
+            if game.fov.is_in_fov(x, y) {
+                game.map[x as usize][y as usize].explored = true;
+            }
+        }
+    }
+}
+
+/// whether the player can see any monster that's still alive
+pub fn monster_in_view(game: &Game, objects: &[Object]) -> bool {
+    objects
+        .iter()
+        .any(|object| object.ai.is_some() && object.alive && game.fov.is_in_fov(object.x, object.y))
+}
+
+/// resolve the rest of the turn after the player acted: the FOV follows the
+/// player, every monster that isn't stunned gets to take its turn, airlocks
+/// nobody's using shut, the player breathes, then everyone's status effects tick
+pub fn monsters_take_turn(game: &mut Game, objects: &mut [Object]) {
+    game.turn += 1;
+    compute_fov(game, objects);
+    for id in 0..objects.len() {
+        if objects[id].ai.is_some() && !has_effect(&objects[id], EffectKind::Stunned) {
+            ai_take_turn(id, game, objects);
+        }
+    }
+    cycle_airlocks(game, objects);
+    breathe(game, objects);
+    tick_effects(game, objects);
+}
+
+/// the stat the player picked to raise on a level up
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LevelUpChoice {
+    Constitution,
+    Strength,
+    Agility,
+}
+
+/// how much experience it takes to get past the given level
+pub fn level_up_xp(level: i32) -> i32 {
+    LEVEL_UP_BASE + level * LEVEL_UP_FACTOR
+}
+
+/// whether the player has the experience for another level
+pub fn can_level_up(objects: &[Object]) -> bool {
+    let player = &objects[PLAYER];
+    player
+        .fighter
+        .is_some_and(|f| f.xp >= level_up_xp(player.level))
+}
+
+/// spend one level's worth of experience and raise the chosen stat
+pub fn level_up(choice: LevelUpChoice, game: &mut Game, objects: &mut [Object]) {
+    let player = &mut objects[PLAYER];
+    let level_up_xp = level_up_xp(player.level);
+    if let Some(fighter) = player.fighter.as_mut() {
+        fighter.xp -= level_up_xp;
+        match choice {
+            LevelUpChoice::Constitution => {
+                fighter.base_max_hp += 20;
+                fighter.hp += 20;
+            }
+            LevelUpChoice::Strength => fighter.base_power += 1,
+            LevelUpChoice::Agility => fighter.base_defense += 1,
+        }
+        player.level += 1;
+        game.messages.add(
+            format!(
+                "Your battle skills grow stronger! You reached level {}!",
+                player.level
+            ),
+            YELLOW,
+        );
+    }
+}
+
+/// take the staircase the player is standing on, if there is one.
+/// Returns whether the player changed levels
+pub fn take_stairs(game: &mut Game, objects: &mut Vec<Object>) -> bool {
+    let (x, y) = objects[PLAYER].pos();
+    match game.map[x as usize][y as usize].kind {
+        TileKind::StairsDown => {
+            game.messages.add(
+                "You climb down the ladder, deeper into the station.",
+                VIOLET,
+            );
+            change_level(game, objects, game.level + 1);
+            true
+        }
+        TileKind::StairsUp => {
+            game.messages.add("You climb back up the ladder.", VIOLET);
+            change_level(game, objects, game.level - 1);
+            true
+        }
+        _ => {
+            game.messages.add("There are no stairs here.", WHITE);
+            false
+        }
+    }
+}
+
+/// put the current level away and move the player to `new_level`, restoring it
+/// if it was visited before and generating it otherwise
+pub fn change_level(game: &mut Game, objects: &mut Vec<Object>, new_level: u32) {
+    let descending = new_level > game.level;
+    let previous = Level {
+        map: mem::take(&mut game.map),
+        objects: objects.drain(PLAYER + 1..).collect(),
+    };
+    game.levels.insert(game.level, previous);
+    game.level = new_level;
+
+    match game.levels.remove(&new_level) {
+        Some(level) => {
+            game.map = level.map;
+            objects.extend(level.objects);
+            // arrive on the other end of the stairs that were taken
+            let arrival = if descending {
+                TileKind::StairsUp
+            } else {
+                TileKind::StairsDown
+            };
+            if let Some((x, y)) = find_tile(&game.map, arrival) {
+                objects[PLAYER].set_pos(x, y);
+            }
+        }
+        None => {
+            let generator = Generator::for_level(new_level, &mut game.rng);
+            game.map = make_map(
+                MAP_HEIGHT,
+                MAP_WIDTH,
+                objects,
+                &mut game.rng,
+                &game.templates,
+                generator,
+                new_level,
+            );
+        }
+    }
+
+    initialise_fov(game);
+    compute_fov(game, objects);
+}
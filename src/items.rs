@@ -0,0 +1,343 @@
+use crate::colors::*;
+use crate::effects::*;
+use crate::object::*;
+use crate::ranged::*;
+use crate::survival::*;
+use crate::structs::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// how many items the player can carry
+pub const MAX_INVENTORY: usize = 26;
+
+const MEDKIT_HEAL_AMOUNT: i32 = 15;
+const STIM_PACK_HEAL_AMOUNT: i32 = 6;
+// and it keeps working for a while after
+const STIM_PACK_REGEN_TURNS: i32 = 5;
+const FLASHBANG_RANGE: i32 = 5;
+const FLASHBANG_NUM_TURNS: i32 = 10;
+const FLASHBANG_STUN_TURNS: i32 = 2;
+
+const PLAYER: usize = 0;
+
+/// what an item does when it's used. Everything else about it, its name and looks and
+/// stats, comes from its template
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Item {
+    Medkit,
+    StimPack,
+    OxygenCanister,
+    Flashbang,
+    Hardsuit,
+    Helmet,
+    Wrench,
+    Crowbar,
+    Pistol,
+    NailGun,
+    AmmoBox,
+    Keycard,
+}
+
+pub enum UseResult {
+    UsedUp,
+    UsedAndKept,
+    Cancelled,
+}
+
+/// where on the player a piece of gear goes. Only one item fits in each
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Slot {
+    Head,
+    Body,
+    Tool,
+    Weapon,
+}
+
+impl fmt::Display for Slot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Slot::Head => write!(f, "head"),
+            Slot::Body => write!(f, "body"),
+            Slot::Tool => write!(f, "tool belt"),
+            Slot::Weapon => write!(f, "hand"),
+        }
+    }
+}
+
+/// an object that can be equipped, yielding bonuses
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Equipment {
+    pub slot: Slot,
+    #[serde(default)]
+    pub equipped: bool,
+    pub power_bonus: i32,
+    pub defense_bonus: i32,
+    pub max_hp_bonus: i32,
+}
+
+/// add the item lying at `object_id` to the player's inventory and remove it from the map.
+/// Returns whether it was picked up
+pub fn pick_item_up(object_id: usize, game: &mut Game, objects: &mut Vec<Object>) -> bool {
+    if game.inventory.len() >= MAX_INVENTORY {
+        game.messages.add(
+            format!(
+                "Your inventory is full, cannot pick up {}.",
+                objects[object_id].name.to_lowercase()
+            ),
+            RED,
+        );
+        false
+    } else {
+        let item = objects.swap_remove(object_id);
+        game.messages
+            .add(format!("You picked up {}.", item.name.to_lowercase()), GREEN);
+        game.inventory.push(item);
+        true
+    }
+}
+
+/// pick up whatever item the player is standing on. Returns whether that took a turn
+pub fn pick_up_at_player(game: &mut Game, objects: &mut Vec<Object>) -> bool {
+    let item_id = objects
+        .iter()
+        .position(|object| object.pos() == objects[PLAYER].pos() && object.item.is_some());
+    match item_id {
+        Some(item_id) => pick_item_up(item_id, game, objects),
+        None => {
+            game.messages.add("There is nothing here to pick up.", WHITE);
+            false
+        }
+    }
+}
+
+/// use the item in the given inventory slot. Returns whether it was used
+pub fn use_item(inventory_id: usize, game: &mut Game, objects: &mut [Object]) -> bool {
+    use Item::*;
+    // just call the "use_function" if it is defined
+    if let Some(item) = game.inventory[inventory_id].item {
+        let on_use = match item {
+            Medkit => use_medkit,
+            StimPack => use_stim_pack,
+            OxygenCanister => use_oxygen_canister,
+            Flashbang => use_flashbang,
+            AmmoBox => use_ammo_box,
+            Keycard => use_keycard,
+            Hardsuit | Helmet | Wrench | Crowbar | Pistol | NailGun => toggle_equipment,
+        };
+        match on_use(inventory_id, game, objects) {
+            UseResult::UsedUp => {
+                // destroy after use, unless it was cancelled for some reason
+                game.inventory.remove(inventory_id);
+                true
+            }
+            UseResult::UsedAndKept => true, // gear stays in the inventory
+            UseResult::Cancelled => {
+                game.messages.add("Cancelled", WHITE);
+                false
+            }
+        }
+    } else {
+        game.messages.add(
+            format!("The {} cannot be used.", game.inventory[inventory_id].name),
+            WHITE,
+        );
+        false
+    }
+}
+
+/// drop the item in the given inventory slot at the player's feet, taking it off first
+pub fn drop_item(inventory_id: usize, game: &mut Game, objects: &mut Vec<Object>) {
+    if game.inventory[inventory_id].equipment.is_some_and(|e| e.equipped) {
+        dequip(inventory_id, game, objects);
+    }
+    let mut item = game.inventory.remove(inventory_id);
+    item.set_pos(objects[PLAYER].x, objects[PLAYER].y);
+    game.messages
+        .add(format!("You dropped {}.", item.name.to_lowercase()), YELLOW);
+    objects.push(item);
+}
+
+fn heal_player(amount: i32, message: &str, game: &mut Game, objects: &mut [Object]) -> UseResult {
+    if let Some(fighter) = objects[PLAYER].fighter {
+        if fighter.hp == objects[PLAYER].max_hp(game) {
+            game.messages.add("You are already at full health.", RED);
+            return UseResult::Cancelled;
+        }
+        game.messages.add(message, LIGHT_GREEN);
+        objects[PLAYER].heal(amount, game);
+        return UseResult::UsedUp;
+    }
+    UseResult::Cancelled
+}
+
+fn use_medkit(_inventory_id: usize, game: &mut Game, objects: &mut [Object]) -> UseResult {
+    heal_player(
+        MEDKIT_HEAL_AMOUNT,
+        "You patch up your wounds with the medkit.",
+        game,
+        objects,
+    )
+}
+
+fn use_stim_pack(_inventory_id: usize, game: &mut Game, objects: &mut [Object]) -> UseResult {
+    let result = heal_player(
+        STIM_PACK_HEAL_AMOUNT,
+        "You jab the stim pack into your thigh. The pain fades a little.",
+        game,
+        objects,
+    );
+    if let UseResult::UsedUp = result {
+        let regen = StatusEffect::new(EffectKind::Regenerating, STIM_PACK_REGEN_TURNS, 1);
+        apply_effect(&mut objects[PLAYER], regen, game);
+    }
+    result
+}
+
+fn use_oxygen_canister(_inventory_id: usize, game: &mut Game, _objects: &mut [Object]) -> UseResult {
+    if game.oxygen >= MAX_OXYGEN {
+        game.messages.add("Your tanks are already full.", RED);
+        return UseResult::Cancelled;
+    }
+    game.messages.add("You plug the canister into your suit and breathe easier.", SKY);
+    refill_oxygen(CANISTER_OXYGEN, game);
+    UseResult::UsedUp
+}
+
+fn use_keycard(_inventory_id: usize, game: &mut Game, _objects: &mut [Object]) -> UseResult {
+    // it gets swiped by walking into a locked door
+    game.messages.add("Walk into a locked door to swipe the keycard.", WHITE);
+    UseResult::Cancelled
+}
+
+fn use_flashbang(_inventory_id: usize, game: &mut Game, objects: &mut [Object]) -> UseResult {
+    // find the closest monster in range, stun it and leave it confused
+    let monster_id = closest_monster(FLASHBANG_RANGE, game, objects);
+    if let Some(monster_id) = monster_id {
+        let old_ai = objects[monster_id].ai.take().unwrap_or_else(Ai::basic);
+        // replace the monster's AI with a "confused" one; after
+        // some turns it will restore the old AI
+        objects[monster_id].ai = Some(Ai::Confused {
+            previous_ai: Box::new(old_ai),
+            turns: FLASHBANG_NUM_TURNS,
+        });
+        game.messages.add(
+            format!(
+                "The flashbang goes off in front of the {}! It reels around blindly.",
+                objects[monster_id].name.to_lowercase()
+            ),
+            LIGHT_GREEN,
+        );
+        let stun = StatusEffect {
+            by_player: true,
+            ..StatusEffect::new(EffectKind::Stunned, FLASHBANG_STUN_TURNS, 0)
+        };
+        apply_effect(&mut objects[monster_id], stun, game);
+        UseResult::UsedUp
+    } else {
+        // no enemy found within maximum range
+        game.messages.add("No enemy is close enough to blind.", RED);
+        UseResult::Cancelled
+    }
+}
+
+/// find the closest monster the player can see, up to a maximum range
+pub fn closest_monster(max_range: i32, game: &Game, objects: &[Object]) -> Option<usize> {
+    let mut closest_enemy = None;
+    let mut closest_dist = (max_range + 1) as f32; // start with (slightly more than) maximum range
+
+    for (id, object) in objects.iter().enumerate() {
+        if (id != PLAYER)
+            && object.fighter.is_some()
+            && object.ai.is_some()
+            && game.fov.is_in_fov(object.x, object.y)
+        {
+            // calculate distance between this object and the player
+            let dist = objects[PLAYER].distance_to(object);
+            if dist < closest_dist {
+                // it's closer, so remember it
+                closest_enemy = Some(id);
+                closest_dist = dist;
+            }
+        }
+    }
+    closest_enemy
+}
+
+fn use_ammo_box(_inventory_id: usize, game: &mut Game, _objects: &mut [Object]) -> UseResult {
+    // reload whatever gun is in the player's hand
+    let weapon_id = match equipped_ranged_weapon(game) {
+        Some(weapon_id) => weapon_id,
+        None => {
+            game.messages.add("You have no gun in hand to load.", RED);
+            return UseResult::Cancelled;
+        }
+    };
+    let weapon = &mut game.inventory[weapon_id];
+    let name = weapon.name.to_lowercase();
+    match weapon.ranged {
+        Some(ref mut ranged) if ranged.max_ammo.is_some_and(|max| ranged.ammo < max) => {
+            ranged.ammo = ranged.max_ammo.unwrap();
+            game.messages.add(format!("You reload the {}.", name), LIGHT_GREEN);
+            UseResult::UsedUp
+        }
+        _ => {
+            game.messages.add(format!("The {} is already fully loaded.", name), RED);
+            UseResult::Cancelled
+        }
+    }
+}
+
+fn toggle_equipment(inventory_id: usize, game: &mut Game, objects: &mut [Object]) -> UseResult {
+    let equipment = match game.inventory[inventory_id].equipment {
+        Some(equipment) => equipment,
+        None => return UseResult::Cancelled,
+    };
+    if equipment.equipped {
+        dequip(inventory_id, game, objects);
+    } else {
+        // if the slot is already being used, take off whatever is there first
+        if let Some(current) = get_equipped_in_slot(equipment.slot, game) {
+            dequip(current, game, objects);
+        }
+        equip(inventory_id, game);
+    }
+    UseResult::UsedAndKept
+}
+
+/// the inventory index of whatever is equipped in `slot`, if anything
+pub fn get_equipped_in_slot(slot: Slot, game: &Game) -> Option<usize> {
+    game.inventory.iter().position(|item| {
+        item.equipment
+            .is_some_and(|e| e.equipped && e.slot == slot)
+    })
+}
+
+/// equip the item in the given inventory slot and show a message about it
+pub fn equip(inventory_id: usize, game: &mut Game) {
+    let item = &mut game.inventory[inventory_id];
+    if let Some(ref mut equipment) = item.equipment {
+        if !equipment.equipped {
+            equipment.equipped = true;
+            let message = format!("Equipped {} on {}.", item.name.to_lowercase(), equipment.slot);
+            game.messages.add(message, LIGHT_GREEN);
+        }
+    }
+}
+
+/// unequip the item in the given inventory slot and show a message about it
+pub fn dequip(inventory_id: usize, game: &mut Game, objects: &mut [Object]) {
+    let item = &mut game.inventory[inventory_id];
+    if let Some(ref mut equipment) = item.equipment {
+        if equipment.equipped {
+            equipment.equipped = false;
+            let message = format!("Took off {} from {}.", item.name.to_lowercase(), equipment.slot);
+            game.messages.add(message, LIGHT_YELLOW);
+        }
+    }
+
+    // losing max HP bonuses can leave the player above the new maximum
+    let max_hp = objects[PLAYER].max_hp(game);
+    if let Some(ref mut fighter) = objects[PLAYER].fighter {
+        fighter.hp = fighter.hp.min(max_hp);
+    }
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// where the frontend looks for the key bindings, relative to the working directory
+pub const KEYBINDINGS_PATH: &str = "data/keybindings.json";
+
+// the shipped bindings, built in for when the file can't be found
+const BUILTIN_KEYBINDINGS: &str = include_str!("../data/keybindings.json");
+
+/// what a key can be bound to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyCommand {
+    MoveNorth,
+    MoveSouth,
+    MoveWest,
+    MoveEast,
+    MoveNorthWest,
+    MoveNorthEast,
+    MoveSouthWest,
+    MoveSouthEast,
+    Wait,
+    Descend,
+    Ascend,
+    PickUp,
+    Inventory,
+    Drop,
+    Fire,
+    CloseDoor,
+    CharacterScreen,
+}
+
+impl KeyCommand {
+    /// which way a move command goes, as (dx, dy)
+    pub fn direction(self) -> Option<(i32, i32)> {
+        use KeyCommand::*;
+        match self {
+            MoveNorth => Some((0, -1)),
+            MoveSouth => Some((0, 1)),
+            MoveWest => Some((-1, 0)),
+            MoveEast => Some((1, 0)),
+            MoveNorthWest => Some((-1, -1)),
+            MoveNorthEast => Some((1, -1)),
+            MoveSouthWest => Some((-1, 1)),
+            MoveSouthEast => Some((1, 1)),
+            _ => None,
+        }
+    }
+}
+
+/// which key does what. Keys are named by the character they type (`"k"`, `">"`) or,
+/// for the ones that don't type anything, by their tcod key code (`"Up"`, `"NumPad7"`)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Keybindings {
+    keys: HashMap<String, KeyCommand>,
+}
+
+impl Keybindings {
+    /// read the bindings from a JSON file like the one shipped in `data/`
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// the bindings the game was built with
+    pub fn builtin() -> Self {
+        serde_json::from_str(BUILTIN_KEYBINDINGS).expect("the built-in key bindings should parse")
+    }
+
+    /// what the named key is bound to, if anything
+    pub fn command(&self, key: &str) -> Option<KeyCommand> {
+        self.keys.get(key).copied()
+    }
+}
@@ -0,0 +1,26 @@
+//! The aeros game core: map, objects, FOV, messages and turn resolution.
+//! Nothing in here needs libtcod or a display, the tcod frontend lives in `main.rs`.
+
+pub mod colors;
+pub mod fov;
+pub mod rng;
+pub mod structs;
+pub mod object;
+pub mod items;
+pub mod maps;
+pub mod mapgen;
+pub mod prefabs;
+pub mod tile;
+pub mod ai;
+pub mod pathfinding;
+pub mod los;
+pub mod ranged;
+pub mod effects;
+pub mod survival;
+pub mod atmosphere;
+pub mod doors;
+pub mod templates;
+pub mod keybindings;
+pub mod command;
+pub mod game;
+pub mod save;
@@ -0,0 +1,54 @@
+use crate::maps::*;
+
+/// the tiles on a straight (Bresenham) line from `from` to `to`, not including `from`
+pub fn line(from: (i32, i32), to: (i32, i32)) -> Vec<(i32, i32)> {
+    let (mut x, mut y) = from;
+    let dx = (to.0 - x).abs();
+    let dy = -(to.1 - y).abs();
+    let step_x = if x < to.0 { 1 } else { -1 };
+    let step_y = if y < to.1 { 1 } else { -1 };
+    let mut error = dx + dy;
+
+    let mut tiles = vec![];
+    while (x, y) != to {
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+        tiles.push((x, y));
+    }
+    tiles
+}
+
+/// like `line`, but keeps going past `to` in the same direction for `length` tiles
+/// in total, stopping at the edge of the map
+pub fn ray(from: (i32, i32), to: (i32, i32), length: i32, map: &Map) -> Vec<(i32, i32)> {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    if (dx, dy) == (0, 0) {
+        return vec![];
+    }
+    // a far enough point on the same line, it passes exactly through `to`
+    let scale = length / dx.abs().max(dy.abs()) + 1;
+    let end = (from.0 + dx * scale, from.1 + dy * scale);
+    let width = map.len() as i32;
+    let height = map.first().map_or(0, |column| column.len()) as i32;
+    line(from, end)
+        .into_iter()
+        .take(length as usize)
+        .take_while(|&(x, y)| x >= 0 && y >= 0 && x < width && y < height)
+        .collect()
+}
+
+/// whether nothing on the map blocks sight between the two points, regardless of
+/// what the player can see
+pub fn line_of_sight(from: (i32, i32), to: (i32, i32), map: &Map) -> bool {
+    line(from, to)
+        .into_iter()
+        .take_while(|&pos| pos != to)
+        .all(|(x, y)| !map[x as usize][y as usize].block_sight)
+}
@@ -1,13 +1,24 @@
+use std::path::Path;
+
 use tcod::console::*;
 use tcod::colors::*;
-use tcod::map::{FovAlgorithm, Map as FovMap};
+use tcod::input::Mouse;
+
+use aeros::command::apply_command;
+use aeros::game::*;
+use aeros::keybindings::*;
+use aeros::maps::{MAP_HEIGHT, MAP_WIDTH};
+use aeros::object::Object;
+use aeros::ranged::*;
+use aeros::rng::GameRng;
+use aeros::save::*;
+use aeros::structs::Game;
+use aeros::survival::*;
+use aeros::templates::*;
+use aeros::tile::TileKind;
 
 mod g_input;
-mod structs;
-mod object;
-mod maps;
-mod tile;
-mod ai;
+mod menus;
 
 // rendering
 const SCREEN_WIDTH: i32 = 80;
@@ -24,15 +35,6 @@ const MSG_HEIGHT: usize = PANEL_HEIGHT as usize - 1;
 const LIMIT_FPS: i32 = 60;
 const PLAYER: usize = 0;
 
-// map stuff
-const MAP_WIDTH: i32 = 80;
-const MAP_HEIGHT: i32 = 43;
-
-// FOV
-const FOV_ALGO: FovAlgorithm = FovAlgorithm::Basic; // default FOV algorithm
-const FOV_LIGHT_WALLS: bool = true; // light walls or not
-const LANTERN_RADIUS: i32 = 50;
-
 // colors
 const COLOR_DARK_WALL: Color = Color { r: 30, g: 30, b: 30 };
 const COLOR_LIGHT_WALL: Color = Color {
@@ -50,7 +52,42 @@ const COLOR_LIGHT_GROUND: Color = Color {
     g: 180,
     b: 180,
 };
+// floors with no air on them
+const COLOR_DARK_VACUUM: Color = Color { r: 5, g: 5, b: 25 };
+const COLOR_LIGHT_VACUUM: Color = Color {
+    r: 110,
+    g: 120,
+    b: 180,
+};
+
+pub struct Tcod {
+    pub root: Root,
+    pub con: Offscreen,
+    pub panel: Offscreen,
+    pub keys: Keybindings,
+    pub mouse: Mouse,
+    /// the rest of the way to where the player last clicked
+    pub travel: Vec<(i32, i32)>,
+}
+
+/// set the color and then draw the character that represents this object at its position
+fn draw_object(object: &Object, con: &mut dyn Console) {
+    con.set_default_foreground(object.color.into());
+    con.put_char(object.x, object.y, object.char, BackgroundFlag::None);
+}
+
+/// the names of everything the player can see under the mouse, comma-separated
+fn get_names_under_mouse(mouse: Mouse, objects: &[Object], game: &Game) -> String {
+    let (x, y) = (mouse.cx as i32, mouse.cy as i32);
+    let names = objects
+        .iter()
+        .filter(|obj| obj.pos() == (x, y) && game.fov.is_in_fov(obj.x, obj.y))
+        .map(|obj| obj.name.clone())
+        .collect::<Vec<_>>();
+    names.join(", ")
+}
 
+#[allow(clippy::too_many_arguments)]
 fn render_bar(
     panel: &mut Offscreen,
     x: i32,
@@ -82,63 +119,83 @@ fn render_bar(
         y,
         BackgroundFlag::None,
         TextAlignment::Center,
-        &format!("{}: {}/{}", name, value, maximum),
+        format!("{}: {}/{}", name, value, maximum),
     );
 }
 
-fn render_all(tcod: &mut structs::Tcod, game: &mut structs::Game, objects: &[object::Object], fov_recompute: bool) {
-    if fov_recompute {
-        // recompute FOV if needed (the player moved or something)
-        let player = &objects[PLAYER];
-        tcod.fov
-            .compute_fov(player.x, player.y, LANTERN_RADIUS, FOV_LIGHT_WALLS, FOV_ALGO);
-    }
-
+pub fn render_all(tcod: &mut Tcod, game: &Game, objects: &[Object]) {
     // go through all tiles, and set their background color
     for y in 0..MAP_HEIGHT {
         for x in 0..MAP_WIDTH {
-            let visible = tcod.fov.is_in_fov(x, y);
+            let visible = game.fov.is_in_fov(x, y);
             let wall = game.map[x as usize][y as usize].block_sight;
-            let color = match (visible, wall) {
+            let vacuum = !game.map[x as usize][y as usize].pressurized;
+            let color = match (visible, wall, vacuum) {
                 // outside of field of view:
-                (false, true) => COLOR_DARK_WALL,
-                (false, false) => COLOR_DARK_GROUND,
+                (false, true, _) => COLOR_DARK_WALL,
+                (false, false, false) => COLOR_DARK_GROUND,
+                (false, false, true) => COLOR_DARK_VACUUM,
                 // inside fov:
-                (true, true) => COLOR_LIGHT_WALL,
-                (true, false) => COLOR_LIGHT_GROUND,
+                (true, true, _) => COLOR_LIGHT_WALL,
+                (true, false, false) => COLOR_LIGHT_GROUND,
+                (true, false, true) => COLOR_LIGHT_VACUUM,
             };
 
-            let explored = &mut game.map[x as usize][y as usize].explored;
-            if visible {
-                // since it's visible, explore it
-                *explored = true;
-            }
-            if *explored {
+            // the core marks visible tiles as explored when it computes the FOV
+            let tile = &game.map[x as usize][y as usize];
+            if tile.explored {
                 // show explored tiles only (any visible tile is explored already)
                 tcod.con
                     .set_char_background(x, y, color, BackgroundFlag::Set);
+
+                // stairs and other fixtures stay drawn once found, so the way out is never lost
+                let fixture = match tile.kind {
+                    TileKind::StairsDown => Some(('>', WHITE)),
+                    TileKind::StairsUp => Some(('<', WHITE)),
+                    TileKind::OxygenStation => Some(('&', WHITE)),
+                    TileKind::HullBreach => Some(('~', WHITE)),
+                    // doors show whether they're open
+                    TileKind::Door if tile.blocked => Some(('+', LIGHT_SEPIA)),
+                    TileKind::Door => Some(('\'', LIGHT_SEPIA)),
+                    TileKind::Airlock if tile.blocked => Some(('+', SKY)),
+                    TileKind::Airlock => Some(('\'', SKY)),
+                    TileKind::LockedDoor => Some(('+', LIGHT_RED)),
+                    _ => None,
+                };
+                if let Some((glyph, glyph_color)) = fixture {
+                    tcod.con.set_default_foreground(if visible { glyph_color } else { COLOR_LIGHT_WALL });
+                    tcod.con.put_char(x, y, glyph, BackgroundFlag::None);
+                }
             }
         }
     }
 
     let mut to_draw: Vec<_> = objects
         .iter()
-        .filter(|o| tcod.fov.is_in_fov(o.x, o.y))
+        .filter(|o| game.fov.is_in_fov(o.x, o.y))
         .collect();
     // sort so that non-blocknig objects come first
-    to_draw.sort_by(|o1, o2| o1.blocks.cmp(&o2.blocks));
+    to_draw.sort_by_key(|o| o.blocks);
     // draw the objects in the list
     for object in &to_draw {
-        object.draw(&mut tcod.con);
+        draw_object(object, &mut tcod.con);
     }
 
     // prepare to render the GUI panel
     tcod.panel.set_default_background(BLACK);
     tcod.panel.clear();
 
+    // show the player's status effects right above the HP bar
+    let mut x = 1;
+    for effect in &objects[PLAYER].effects {
+        tcod.panel.set_default_foreground(effect.kind.color().into());
+        tcod.panel.print_ex(x, 0, BackgroundFlag::None, TextAlignment::Left, effect.kind.tag());
+        x += effect.kind.tag().len() as i32 + 1;
+    }
+
     // show the player's stats
     let hp = objects[PLAYER].fighter.map_or(0, |f| f.hp);
-    let max_hp = objects[PLAYER].fighter.map_or(0, |f| f.max_hp);
+    let max_hp = objects[PLAYER].max_hp(game);
     render_bar(
         &mut tcod.panel,
         1,
@@ -151,6 +208,79 @@ fn render_all(tcod: &mut structs::Tcod, game: &mut structs::Game, objects: &[obj
         DARKER_RED,
     );
 
+    render_bar(
+        &mut tcod.panel,
+        1,
+        2,
+        BAR_WIDTH,
+        "O2",
+        game.oxygen,
+        MAX_OXYGEN,
+        SKY,
+        DARKER_SKY,
+    );
+
+    // the suit's bar only shows while one is worn
+    if let Some(integrity) = worn_suit(game).and_then(|id| game.inventory[id].integrity) {
+        render_bar(
+            &mut tcod.panel,
+            1,
+            3,
+            BAR_WIDTH,
+            "Suit",
+            integrity.current,
+            integrity.max,
+            LIGHT_ORANGE,
+            DARKER_ORANGE,
+        );
+    }
+
+    let xp = objects[PLAYER].fighter.map_or(0, |f| f.xp);
+    render_bar(
+        &mut tcod.panel,
+        1,
+        4,
+        BAR_WIDTH,
+        "XP",
+        xp,
+        level_up_xp(objects[PLAYER].level),
+        LIGHT_VIOLET,
+        DARKER_VIOLET,
+    );
+
+    tcod.panel.set_default_foreground(LIGHT_GREY);
+    tcod.panel.print_ex(
+        1,
+        5,
+        BackgroundFlag::None,
+        TextAlignment::Left,
+        format!("Dungeon level: {}", game.level),
+    );
+
+    // show what's left in the gun in hand
+    if let Some(weapon_id) = equipped_ranged_weapon(game) {
+        let weapon = &game.inventory[weapon_id];
+        if let Some(RangedWeapon { ammo, max_ammo: Some(max_ammo), .. }) = weapon.ranged {
+            tcod.panel.print_ex(
+                1,
+                6,
+                BackgroundFlag::None,
+                TextAlignment::Left,
+                format!("{}: {}/{}", weapon.name, ammo, max_ammo),
+            );
+        }
+    }
+
+    // what's under the mouse, on the line below the messages
+    tcod.panel.set_default_foreground(LIGHT_GREY);
+    tcod.panel.print_ex(
+        MSG_X,
+        PANEL_HEIGHT - 1,
+        BackgroundFlag::None,
+        TextAlignment::Left,
+        get_names_under_mouse(tcod.mouse, objects, game),
+    );
+
     // print the game messages, one line at a time
     let mut y = MSG_HEIGHT as i32;
     for &(ref msg, color) in game.messages.iter().rev() {
@@ -159,7 +289,7 @@ fn render_all(tcod: &mut structs::Tcod, game: &mut structs::Game, objects: &[obj
         if y < 0 {
             break;
         }
-        tcod.panel.set_default_foreground(color);
+        tcod.panel.set_default_foreground(color.into());
         tcod.panel.print_rect(MSG_X, y, MSG_WIDTH, 0, msg);
     }
 
@@ -187,81 +317,163 @@ fn render_all(tcod: &mut structs::Tcod, game: &mut structs::Game, objects: &[obj
     );
 }
 
-fn main() {
-    let root = Root::initializer()
-    .font("dejavu16x16_gs_tc.png", FontLayout::Tcod)
-    .font_type(FontType::Greyscale)
-    .size(SCREEN_WIDTH, SCREEN_HEIGHT)
-    .title("Aeros")
-    .init();
-    
-    let mut player = object::Object::new(25, 23, '@', "You", DESATURATED_GREEN, false);
-    player.fighter = Some(object::Fighter {
-        max_hp: 30,
-        hp: 30,
-        defense: 2,
-        power: 5,
-        on_death: object::DeathCallback::Player,
-    });
-    player.alive = true;
-    player.attack_message = "attack".into();
-
-    let mut objects = vec![player];
-    let mut game = structs::Game {
-        map: maps::make_map(MAP_HEIGHT, MAP_WIDTH, &mut objects),
-        messages: structs::Messages::new()
-    };
-
-    game.messages.add(
-        "Welcome to space. Wear your hardsuit and prepare some medicine.",
-        RED,
-    );
-
-    let mut tcod = structs::Tcod { 
-        root, 
-        con: Offscreen::new(MAP_WIDTH, MAP_HEIGHT),
-        panel: Offscreen::new(SCREEN_WIDTH, PANEL_HEIGHT), 
-        fov: FovMap::new(MAP_WIDTH, MAP_HEIGHT),
-    };
-    tcod::system::set_fps(LIMIT_FPS);
-
-    for y in 0..MAP_HEIGHT {
-        for x in 0..MAP_WIDTH { 
-            tcod.fov.set(
-                x,
-                y,
-                !game.map[x as usize][y as usize].block_sight,
-                !game.map[x as usize][y as usize].blocked,
-            );
+/// read `--seed <n>` or `--seed=<n>` from the command line, if given
+fn seed_from_args() -> Option<u64> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = if arg == "--seed" {
+            args.next()
+        } else if let Some(value) = arg.strip_prefix("--seed=") {
+            Some(value.to_string())
+        } else {
+            continue;
+        };
+        match value.as_deref().map(str::parse) {
+            Some(Ok(seed)) => return Some(seed),
+            _ => {
+                eprintln!("--seed expects a number, e.g. --seed 12345");
+                std::process::exit(1);
+            }
         }
     }
+    None
+}
 
-    // force FOV "recompute" first time through the game loop
-    let mut previous_player_position = (-1, -1);
-
+/// run the turn loop until the player quits, dies or closes the window. Leaving saves
+/// the game, dying shows the death screen and throws the save away
+fn play_game(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>) {
+    // don't carry on travelling from a game that's been left
+    tcod.travel.clear();
     while !tcod.root.window_closed() {
         // refresh the console
         tcod.con.clear();
 
         // render it
-        let fov_recompute = previous_player_position != (objects[PLAYER].pos());
-        render_all(&mut tcod, &mut game, &objects, fov_recompute);
+        render_all(tcod, game, objects);
         tcod.root.flush();
 
-        previous_player_position = objects[PLAYER].pos();
-        let player_action = g_input::handle_input(&mut tcod, &mut game, &mut objects);
+        let took_turn = match g_input::handle_input(tcod, game, objects) {
+            g_input::PlayerAction::Exit => break,
+            g_input::PlayerAction::Act(command) => apply_command(command, game, objects),
+            g_input::PlayerAction::DidntTakeTurn => false,
+        };
 
-        if player_action == g_input::PlayerAction::Exit {
+        // let monsters take their turn
+        if objects[PLAYER].alive && took_turn {
+            monsters_take_turn(game, objects);
+        }
+
+        // spend any experience the player earned this turn
+        while objects[PLAYER].alive && can_level_up(objects) {
+            tcod.con.clear();
+            render_all(tcod, game, objects);
+            let choice = menus::level_up_menu(&objects[PLAYER], &mut tcod.root);
+            level_up(choice, game, objects);
+        }
+
+        if !objects[PLAYER].alive {
+            // one last look at what happened, then the summary
+            tcod.con.clear();
+            render_all(tcod, game, objects);
+            menus::death_screen(game, &mut tcod.root);
             break;
         }
+    }
 
-        // let monsters take their turn
-        if objects[PLAYER].alive && player_action != g_input::PlayerAction::DidntTakeTurn {
-            for id in 0..objects.len() {
-                if objects[id].ai.is_some() {
-                    ai::ai_take_turn(id, &tcod, &mut game, &mut objects);
+    // death is permanent, everything else saves on the way out
+    let path = Path::new(SAVE_PATH);
+    let saved = if objects[PLAYER].alive {
+        save_game(path, game, objects)
+    } else {
+        delete_save(path)
+    };
+    if let Err(e) = saved {
+        eprintln!("Couldn't save the game: {}", e);
+    }
+}
+
+fn main_menu(tcod: &mut Tcod) {
+    // only the first new game uses the seed from the command line
+    let mut seed = seed_from_args();
+
+    while !tcod.root.window_closed() {
+        // show the game's title
+        tcod.root.set_default_background(BLACK);
+        tcod.root.clear();
+        tcod.root.set_default_foreground(LIGHT_YELLOW);
+        tcod.root.print_ex(
+            SCREEN_WIDTH / 2,
+            SCREEN_HEIGHT / 2 - 6,
+            BackgroundFlag::None,
+            TextAlignment::Center,
+            "AEROS",
+        );
+        tcod.root.print_ex(
+            SCREEN_WIDTH / 2,
+            SCREEN_HEIGHT / 2 - 4,
+            BackgroundFlag::None,
+            TextAlignment::Center,
+            "A roguelike in space",
+        );
+
+        // show options and wait for the player's choice
+        let choices = &["New game", "Continue", "Quit"];
+        let choice = menus::menu("", choices, 24, &mut tcod.root);
+
+        match choice {
+            Some(0) => {
+                // new game
+                let seed = seed.take().unwrap_or_else(GameRng::random_seed);
+                // read the templates again every time, so edits show up without a restart
+                let templates = Templates::load(Path::new(TEMPLATES_PATH)).unwrap_or_else(|e| {
+                    eprintln!("Couldn't load {}, using the built-in templates: {}", TEMPLATES_PATH, e);
+                    Templates::builtin()
+                });
+                let (mut game, mut objects) = new_game(seed, templates);
+                play_game(tcod, &mut game, &mut objects);
+            }
+            Some(1) => {
+                // load game
+                match load_game(Path::new(SAVE_PATH)) {
+                    Ok((mut game, mut objects)) => {
+                        play_game(tcod, &mut game, &mut objects);
+                    }
+                    Err(e) => {
+                        let text = format!("\nCouldn't load a saved game: {}\n", e);
+                        menus::msgbox(&text, 40, &mut tcod.root);
+                    }
                 }
             }
+            Some(2) => {
+                // quit
+                break;
+            }
+            _ => {}
         }
     }
 }
+
+fn main() {
+    let root = Root::initializer()
+    .font("dejavu16x16_gs_tc.png", FontLayout::Tcod)
+    .font_type(FontType::Greyscale)
+    .size(SCREEN_WIDTH, SCREEN_HEIGHT)
+    .title("Aeros")
+    .init();
+
+    let keys = Keybindings::load(Path::new(KEYBINDINGS_PATH)).unwrap_or_else(|e| {
+        eprintln!("Couldn't load {}, using the built-in key bindings: {}", KEYBINDINGS_PATH, e);
+        Keybindings::builtin()
+    });
+    let mut tcod = Tcod {
+        root,
+        con: Offscreen::new(MAP_WIDTH, MAP_HEIGHT),
+        panel: Offscreen::new(SCREEN_WIDTH, PANEL_HEIGHT),
+        keys,
+        mouse: Default::default(),
+        travel: vec![],
+    };
+    tcod::system::set_fps(LIMIT_FPS);
+
+    main_menu(&mut tcod);
+}
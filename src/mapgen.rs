@@ -0,0 +1,470 @@
+use crate::maps::*;
+use crate::rng::GameRng;
+use crate::tile::*;
+use rand::Rng;
+use std::collections::VecDeque;
+
+// random rooms
+const ROOM_MAX_SIZE: i32 = 10;
+const ROOM_MIN_SIZE: i32 = 6;
+const MAX_ROOMS: i32 = 30;
+
+// station decks: the map gets split until the parts are smaller than twice this
+const MIN_PARTITION_SIZE: i32 = 10;
+
+// asteroid caves
+const CAVE_WALL_CHANCE: f32 = 0.45;
+const CAVE_SMOOTHING_STEPS: i32 = 5;
+
+// wreckage: how much of the map the walk digs out
+const WRECKAGE_FLOOR_FRACTION: f32 = 0.35;
+
+// a level with fewer rooms than this gets thrown away and generated again, up to so many times
+const MIN_ROOMS: usize = 5;
+const MAX_GENERATE_TRIES: i32 = 20;
+
+// extra tunnels between neighbouring rooms, so not every level is one long line
+const EXTRA_LOOPS: i32 = 3;
+
+// maps without rooms get this many areas around floor tiles instead, of this radius
+const AREA_COUNT: usize = 12;
+const AREA_RADIUS: i32 = 3;
+
+// how likely each generator is, from each level on
+const GENERATOR_CHANCES: &[(Generator, &[Transition])] = &[
+    (
+        Generator::Rooms,
+        &[Transition { level: 1, value: 100 }, Transition { level: 2, value: 30 }],
+    ),
+    (Generator::StationDeck, &[Transition { level: 2, value: 40 }]),
+    (
+        Generator::Caves,
+        &[Transition { level: 3, value: 20 }, Transition { level: 6, value: 30 }],
+    ),
+    (
+        Generator::Wreckage,
+        &[Transition { level: 4, value: 15 }, Transition { level: 7, value: 25 }],
+    ),
+];
+
+/// the ways a level can be laid out
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Generator {
+    /// random rectangular rooms joined by L-shaped tunnels
+    Rooms,
+    /// a deck split up by binary space partitioning, with a room in every part
+    StationDeck,
+    /// asteroid caves grown with a cellular automaton
+    Caves,
+    /// derelict wreckage dug out by a drunkard's walk
+    Wreckage,
+}
+
+/// a freshly generated level. The player starts in the first room and the way down
+/// goes in the last. Caves and wreckage have no real rooms, theirs are areas around
+/// floor tiles to spread things over, and they don't get doors (`walled` is false)
+pub struct Layout {
+    pub map: Map,
+    pub rooms: Vec<Rect>,
+    pub walled: bool,
+}
+
+impl Generator {
+    /// pick a generator for dungeon level `depth`. The first level is always plain rooms
+    pub fn for_level(depth: u32, rng: &mut GameRng) -> Self {
+        let chances: Vec<(Generator, u32)> = GENERATOR_CHANCES
+            .iter()
+            .map(|&(generator, table)| (generator, from_dungeon_level(table, depth)))
+            .filter(|&(_, chance)| chance > 0)
+            .collect();
+        random_choice(&chances, rng)
+    }
+
+    /// lay out a level of the given size, with a few loops in it if it has rooms.
+    /// Levels with too few rooms are thrown away and tried again
+    pub fn generate(self, width: i32, height: i32, rng: &mut GameRng) -> Layout {
+        use Generator::*;
+        let generate = match self {
+            Rooms => rooms_and_tunnels,
+            StationDeck => station_deck,
+            Caves => caves,
+            Wreckage => wreckage,
+        };
+        let mut layout = generate(width, height, rng);
+        for _ in 1..MAX_GENERATE_TRIES {
+            if layout.rooms.len() >= MIN_ROOMS {
+                break;
+            }
+            layout = generate(width, height, rng);
+        }
+        // the level needs somewhere to put the player and the stairs, and the first
+        // room of a rooms-and-tunnels map always fits
+        if layout.rooms.is_empty() {
+            layout = rooms_and_tunnels(width, height, rng);
+        }
+        if layout.walled {
+            add_loops(&layout.rooms, &mut layout.map, rng);
+        }
+        layout
+    }
+}
+
+fn solid_map(width: i32, height: i32) -> Map {
+    vec![vec![Tile::wall(); height as usize]; width as usize]
+}
+
+/// join two rooms with an L-shaped tunnel between their centers
+fn connect_rooms(a: Rect, b: Rect, map: &mut Map, rng: &mut GameRng) {
+    let (a_x, a_y) = a.center();
+    let (b_x, b_y) = b.center();
+    // toss a coin (random bool value -- either true or false)
+    if rng.gen() {
+        // first move horizontally, then vertically
+        create_h_tunnel(a_x, b_x, a_y, map);
+        create_v_tunnel(a_y, b_y, b_x, map);
+    } else {
+        // first move vertically, then horizontally
+        create_v_tunnel(a_y, b_y, a_x, map);
+        create_h_tunnel(a_x, b_x, b_y, map);
+    }
+}
+
+/// join a few random rooms to their nearest neighbour, unless that's the room they
+/// were already joined to when the level was laid out
+fn add_loops(rooms: &[Rect], map: &mut Map, rng: &mut GameRng) {
+    if rooms.len() < 3 {
+        return;
+    }
+    for _ in 0..EXTRA_LOOPS {
+        let index = rng.gen_range(0, rooms.len());
+        let (x, y) = rooms[index].center();
+        let nearest = (0..rooms.len())
+            .filter(|&other| other != index && other + 1 != index && other != index + 1)
+            .min_by_key(|&other| {
+                let (other_x, other_y) = rooms[other].center();
+                (other_x - x).pow(2) + (other_y - y).pow(2)
+            });
+        if let Some(other) = nearest {
+            connect_rooms(rooms[index], rooms[other], map, rng);
+        }
+    }
+}
+
+fn rooms_and_tunnels(width: i32, height: i32, rng: &mut GameRng) -> Layout {
+    let mut map = solid_map(width, height);
+    let mut rooms: Vec<Rect> = vec![];
+
+    for _ in 0..MAX_ROOMS {
+        // random width and height
+        let w = rng.gen_range(ROOM_MIN_SIZE, ROOM_MAX_SIZE + 1);
+        let h = rng.gen_range(ROOM_MIN_SIZE, ROOM_MAX_SIZE + 1);
+        // random position without going out of the boundaries of the map
+        let x = rng.gen_range(0, width - w);
+        let y = rng.gen_range(0, height - h);
+
+        let new_room = Rect::new(x, y, w, h);
+
+        // run through the other rooms and see if they intersect with this one
+        let failed = rooms
+            .iter()
+            .any(|other_room| new_room.intersects_with(other_room));
+
+        if !failed {
+            // this means there are no intersections, so this room is valid
+            create_room(new_room, &mut map);
+            // connect it to the previous room with a tunnel
+            if let Some(&previous) = rooms.last() {
+                connect_rooms(previous, new_room, &mut map, rng);
+            }
+            rooms.push(new_room);
+        }
+    }
+
+    Layout {
+        map,
+        rooms,
+        walled: true,
+    }
+}
+
+fn station_deck(width: i32, height: i32, rng: &mut GameRng) -> Layout {
+    let mut map = solid_map(width, height);
+    let mut rooms = vec![];
+    split_deck(Rect::new(0, 0, width - 1, height - 1), &mut map, &mut rooms, rng);
+    Layout {
+        map,
+        rooms,
+        walled: true,
+    }
+}
+
+/// split the part of the deck in two, over and over, then put a room in each of the
+/// smallest parts and join the halves back up. Returns a room in the part to connect to
+fn split_deck(part: Rect, map: &mut Map, rooms: &mut Vec<Rect>, rng: &mut GameRng) -> Rect {
+    let width = part.x2 - part.x1;
+    let height = part.y2 - part.y1;
+    let split_across = width >= 2 * MIN_PARTITION_SIZE;
+    let split_down = height >= 2 * MIN_PARTITION_SIZE;
+
+    if !split_across && !split_down {
+        // small enough, put a room in it
+        let w = rng.gen_range(ROOM_MIN_SIZE, width.min(ROOM_MAX_SIZE) + 1);
+        let h = rng.gen_range(ROOM_MIN_SIZE, height.min(ROOM_MAX_SIZE) + 1);
+        let x = rng.gen_range(part.x1, part.x2 - w + 1);
+        let y = rng.gen_range(part.y1, part.y2 - h + 1);
+        let room = Rect::new(x, y, w, h);
+        create_room(room, map);
+        rooms.push(room);
+        return room;
+    }
+
+    // cut the longer way when there's a choice
+    let (first, second) = if split_across && (!split_down || width >= height) {
+        let x = rng.gen_range(part.x1 + MIN_PARTITION_SIZE, part.x2 - MIN_PARTITION_SIZE + 1);
+        (Rect { x2: x, ..part }, Rect { x1: x, ..part })
+    } else {
+        let y = rng.gen_range(part.y1 + MIN_PARTITION_SIZE, part.y2 - MIN_PARTITION_SIZE + 1);
+        (Rect { y2: y, ..part }, Rect { y1: y, ..part })
+    };
+    let first_room = split_deck(first, map, rooms, rng);
+    let second_room = split_deck(second, map, rooms, rng);
+    connect_rooms(first_room, second_room, map, rng);
+    if rng.gen() {
+        first_room
+    } else {
+        second_room
+    }
+}
+
+fn caves(width: i32, height: i32, rng: &mut GameRng) -> Layout {
+    // start from noise, the edge of the map stays solid
+    let mut walls = vec![vec![true; height as usize]; width as usize];
+    for x in 1..width - 1 {
+        for y in 1..height - 1 {
+            walls[x as usize][y as usize] = rng.gen::<f32>() < CAVE_WALL_CHANCE;
+        }
+    }
+
+    // then smooth it out: a tile turns to rock if most of what's around it is rock
+    for _ in 0..CAVE_SMOOTHING_STEPS {
+        let mut next = walls.clone();
+        for x in 1..width - 1 {
+            for y in 1..height - 1 {
+                let mut rock = 0;
+                for dx in -1..=1 {
+                    for dy in -1..=1 {
+                        if walls[(x + dx) as usize][(y + dy) as usize] {
+                            rock += 1;
+                        }
+                    }
+                }
+                next[x as usize][y as usize] = rock >= 5;
+            }
+        }
+        walls = next;
+    }
+
+    let mut map = solid_map(width, height);
+    for x in 0..width {
+        for y in 0..height {
+            if !walls[x as usize][y as usize] {
+                map[x as usize][y as usize] = Tile::empty();
+            }
+        }
+    }
+    keep_largest_cave(&mut map);
+
+    let rooms = areas(&map, rng);
+    Layout {
+        map,
+        rooms,
+        walled: false,
+    }
+}
+
+/// fill in every pocket of floor but the biggest, so the whole cave can be walked
+fn keep_largest_cave(map: &mut Map) {
+    let width = map.len();
+    let height = map[0].len();
+    let mut region = vec![vec![None; height]; width];
+    let mut sizes = vec![];
+    for x in 0..width {
+        for y in 0..height {
+            if map[x][y].blocked || region[x][y].is_some() {
+                continue;
+            }
+            let id = sizes.len();
+            let mut size = 0;
+            let mut frontier = vec![(x, y)];
+            region[x][y] = Some(id);
+            while let Some((x, y)) = frontier.pop() {
+                size += 1;
+                for &(dx, dy) in NEIGHBOURS.iter() {
+                    // the edge of the map is solid, so this never goes off it
+                    let (nx, ny) = ((x as i32 + dx) as usize, (y as i32 + dy) as usize);
+                    if !map[nx][ny].blocked && region[nx][ny].is_none() {
+                        region[nx][ny] = Some(id);
+                        frontier.push((nx, ny));
+                    }
+                }
+            }
+            sizes.push(size);
+        }
+    }
+
+    let largest = (0..sizes.len()).max_by_key(|&id| sizes[id]);
+    for x in 0..width {
+        for y in 0..height {
+            if region[x][y].is_some() && region[x][y] != largest {
+                map[x][y] = Tile::wall();
+            }
+        }
+    }
+}
+
+fn wreckage(width: i32, height: i32, rng: &mut GameRng) -> Layout {
+    let mut map = solid_map(width, height);
+    let target = ((width * height) as f32 * WRECKAGE_FLOOR_FRACTION) as i32;
+
+    // stagger around from the middle, digging out wherever the walk goes
+    let (mut x, mut y) = (width / 2, height / 2);
+    let mut dug = 0;
+    while dug < target {
+        if map[x as usize][y as usize].blocked {
+            map[x as usize][y as usize] = Tile::empty();
+            dug += 1;
+        }
+        match rng.gen_range(0, 4) {
+            0 => x += 1,
+            1 => x -= 1,
+            2 => y += 1,
+            _ => y -= 1,
+        }
+        // keep off the edge of the map
+        x = x.clamp(1, width - 2);
+        y = y.clamp(1, height - 2);
+    }
+
+    let rooms = areas(&map, rng);
+    Layout {
+        map,
+        rooms,
+        walled: false,
+    }
+}
+
+/// how many steps it takes to walk to every tile from `start`, `None` where it can't be
+/// reached. Doors count as open, the player can get through them
+pub fn walking_distances(map: &Map, start: (i32, i32)) -> Vec<Vec<Option<i32>>> {
+    let width = map.len() as i32;
+    let height = map[0].len() as i32;
+    let mut distances = vec![vec![None; height as usize]; width as usize];
+    let mut frontier = VecDeque::new();
+    distances[start.0 as usize][start.1 as usize] = Some(0);
+    frontier.push_back(start);
+    while let Some((x, y)) = frontier.pop_front() {
+        let distance = distances[x as usize][y as usize].unwrap_or(0);
+        for (nx, ny) in NEIGHBOURS.iter().map(|&(dx, dy)| (x + dx, y + dy)) {
+            if nx < 0 || ny < 0 || nx >= width || ny >= height {
+                continue;
+            }
+            if walkable(&map[nx as usize][ny as usize]) && distances[nx as usize][ny as usize].is_none() {
+                distances[nx as usize][ny as usize] = Some(distance + 1);
+                frontier.push_back((nx, ny));
+            }
+        }
+    }
+    distances
+}
+
+fn walkable(tile: &Tile) -> bool {
+    !tile.blocked || tile.is_door()
+}
+
+/// make sure every tile that can be walked on can be walked to from `start`: any part of
+/// the level that's cut off gets a tunnel dug from it to the nearest part that isn't
+pub fn connect_regions(map: &mut Map, start: (i32, i32)) {
+    let width = map.len() as i32;
+    let height = map[0].len() as i32;
+    loop {
+        let reached = walking_distances(map, start);
+        let cut_off = (0..width)
+            .flat_map(|x| (0..height).map(move |y| (x, y)))
+            .find(|&(x, y)| walkable(&map[x as usize][y as usize]) && reached[x as usize][y as usize].is_none());
+        let (x, y) = match cut_off {
+            Some(tile) => tile,
+            None => return,
+        };
+
+        // look for the nearest tile that can be reached, through the rock if need be. The
+        // digging goes straight up, down or across, so the tunnel is easy to walk
+        let mut came_from = vec![vec![None; height as usize]; width as usize];
+        let mut frontier = VecDeque::new();
+        came_from[x as usize][y as usize] = Some((x, y));
+        frontier.push_back((x, y));
+        let mut found = None;
+        while let Some((cx, cy)) = frontier.pop_front() {
+            if reached[cx as usize][cy as usize].is_some() {
+                found = Some((cx, cy));
+                break;
+            }
+            for (nx, ny) in [(cx - 1, cy), (cx + 1, cy), (cx, cy - 1), (cx, cy + 1)] {
+                // the edge of the map stays solid
+                if nx < 1 || ny < 1 || nx >= width - 1 || ny >= height - 1 {
+                    continue;
+                }
+                if came_from[nx as usize][ny as usize].is_none() {
+                    came_from[nx as usize][ny as usize] = Some((cx, cy));
+                    frontier.push_back((nx, ny));
+                }
+            }
+        }
+
+        match found {
+            // dig back along the way to the cut off part
+            Some(mut step) => {
+                while step != (x, y) {
+                    if map[step.0 as usize][step.1 as usize].blocked && !map[step.0 as usize][step.1 as usize].is_door() {
+                        map[step.0 as usize][step.1 as usize] = Tile::empty();
+                    }
+                    step = came_from[step.0 as usize][step.1 as usize].unwrap_or((x, y));
+                }
+            }
+            // there's nowhere to get to from here (e.g. the start is walled in), so fill it in
+            None => map[x as usize][y as usize] = Tile::wall(),
+        }
+    }
+}
+
+/// rooms for a map that has none: squares around floor tiles, centered on them. The first
+/// is at a random spot and the last as far a walk from it as it gets. None at all if
+/// there's no floor to put them on
+fn areas(map: &Map, rng: &mut GameRng) -> Vec<Rect> {
+    let width = map.len() as i32;
+    let height = map[0].len() as i32;
+    // keep clear of the edge so the squares stay on the map
+    let floors: Vec<(i32, i32)> = (AREA_RADIUS..width - AREA_RADIUS)
+        .flat_map(|x| (AREA_RADIUS..height - AREA_RADIUS).map(move |y| (x, y)))
+        .filter(|&(x, y)| !map[x as usize][y as usize].blocked)
+        .collect();
+    if floors.is_empty() {
+        return vec![];
+    }
+    let start = floors[rng.gen_range(0, floors.len())];
+    let distances = walking_distances(map, start);
+    let furthest = floors
+        .iter()
+        .copied()
+        .max_by_key(|&(x, y)| distances[x as usize][y as usize])
+        .unwrap_or(start);
+
+    let mut centers = vec![start];
+    for _ in 2..AREA_COUNT {
+        centers.push(floors[rng.gen_range(0, floors.len())]);
+    }
+    centers.push(furthest);
+    centers
+        .into_iter()
+        .map(|(x, y)| Rect::new(x - AREA_RADIUS, y - AREA_RADIUS, 2 * AREA_RADIUS, 2 * AREA_RADIUS))
+        .collect()
+}
@@ -1,87 +1,254 @@
-use crate::tile::Tile;
+use crate::tile::*;
+use crate::atmosphere::vent_breaches;
 use crate::object::*;
-use tcod::colors::*;
+use crate::items::Item;
+use crate::rng::GameRng;
+use crate::templates::Templates;
+use crate::mapgen::*;
+use crate::prefabs::place_prefabs;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::cmp;
 
 //parameters for dungeon generator
-const MAX_ROOM_MONSTERS: i32 = 3;
-const ROOM_MAX_SIZE: i32 = 10;
-const ROOM_MIN_SIZE: i32 = 6;
-const MAX_ROOMS: i32 = 30;
+// the most monsters and items a room can have, going up the deeper it is
+const MAX_ROOM_MONSTERS: &[Transition] = &[
+    Transition { level: 1, value: 2 },
+    Transition { level: 4, value: 3 },
+    Transition { level: 6, value: 5 },
+];
+const MAX_ROOM_ITEMS: &[Transition] = &[
+    Transition { level: 1, value: 1 },
+    Transition { level: 4, value: 2 },
+];
+// chance of a hull breach on a level, going up with every level below the first
+const BREACH_CHANCE_PER_DEPTH: f32 = 0.15;
+const MAX_BREACH_CHANCE: f32 = 0.6;
+// how many rooms to try before giving up on one that can be sealed off
+const BREACH_TRIES: i32 = 5;
+// chance of each gap in a room's walls getting a door, and of which kind
+const LOCKED_DOOR_CHANCE: f32 = 0.05;
+const AIRLOCK_CHANCE: f32 = 0.1;
+const DOOR_CHANCE: f32 = 0.4;
 const PLAYER: usize = 0;
 
+// map size
+pub const MAP_WIDTH: i32 = 80;
+pub const MAP_HEIGHT: i32 = 43;
+
 // maps part
 pub type Map = Vec<Vec<Tile>>;
 
-pub fn make_map(map_height: i32, map_width: i32, objects: &mut Vec<Object>) -> Map {
-    let mut map = vec![vec![Tile::wall(); map_height as usize]; map_width as usize];
-    let mut rooms = vec![];
+/// the tiles next to a tile, diagonals included. Everything that walks, and the air,
+/// gets from one tile to the next this way
+pub const NEIGHBOURS: [(i32, i32); 8] = [
+    (0, -1),
+    (0, 1),
+    (-1, 0),
+    (1, 0),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+    (1, 1),
+];
 
-    for _ in 0..MAX_ROOMS {
-        // random width and height
-        let w = rand::thread_rng().gen_range(ROOM_MIN_SIZE, ROOM_MAX_SIZE + 1);
-        let h = rand::thread_rng().gen_range(ROOM_MIN_SIZE, ROOM_MAX_SIZE + 1);
-        // random position without going out of the boundaries of the map
-        let x = rand::thread_rng().gen_range(0, map_width - w);
-        let y = rand::thread_rng().gen_range(0, map_height - h);
+/// generate dungeon level `depth` (starting at 1) with the given generator, fill it
+/// with monsters and items and move the player to its first room
+pub fn make_map(
+    map_height: i32,
+    map_width: i32,
+    objects: &mut Vec<Object>,
+    rng: &mut GameRng,
+    templates: &Templates,
+    generator: Generator,
+    depth: u32,
+) -> Map {
+    let Layout {
+        mut map,
+        rooms,
+        walled,
+    } = generator.generate(map_width, map_height, rng);
 
-        let new_room = Rect::new(x, y, w, h);
-    
-    
-        // run through the other rooms and see if they intersect with this one
-        let failed = rooms
-            .iter()
-            .any(|other_room| new_room.intersects_with(other_room));
-
-        if !failed {
-            // this means there are no intersections, so this room is valid
-
-            // "paint" it to the map's tiles
-            create_room(new_room, &mut map);
-
-            place_objects(new_room, &mut map, objects);
-
-
-            // center coordinates of the new room, will be useful later
-            let (new_x, new_y) = new_room.center();
-
-            if rooms.is_empty() {
-                // this is the first room, where the player starts at
-                objects[PLAYER].set_pos(new_x, new_y);
-            } else {
-                // all rooms after the first:
-                // connect it to the previous room with a tunnel
-            
-                // center coordinates of the previous room
-                let (prev_x, prev_y) = rooms[rooms.len() - 1].center();
-            
-                // toss a coin (random bool value -- either true or false)
-                if rand::random() {
-                    // first move horizontally, then vertically
-                    create_h_tunnel(prev_x, new_x, prev_y, &mut map);
-                    create_v_tunnel(prev_y, new_y, new_x, &mut map);
-                } else {
-                    // first move vertically, then horizontally
-                    create_v_tunnel(prev_y, new_y, prev_x, &mut map);
-                    create_h_tunnel(prev_x, new_x, new_y, &mut map);
-                }
+    for &room in &rooms {
+        place_objects(room, &map, objects, rng, templates, depth);
+    }
+    // hand-drawn rooms go into whatever rock is left, tunnelled into the rest
+    place_prefabs(&mut map, objects, rng, templates, depth);
+    // the player starts in the first room
+    let (start_x, start_y) = rooms[0].center();
+    objects[PLAYER].set_pos(start_x, start_y);
+
+    // stairs go in after the tunnels so none get dug over. Every level but the
+    // first has a way back up where the player starts, the way down is in the last room
+    if depth > 1 {
+        let (first_room_x, first_room_y) = rooms[0].center();
+        map[first_room_x as usize][first_room_y as usize] = Tile::stairs_up();
+    }
+    let (last_room_x, last_room_y) = rooms[rooms.len() - 1].center();
+    map[last_room_x as usize][last_room_y as usize] = Tile::stairs_down();
+
+    // whatever got cut off from the start, the stairs included, gets tunnelled back to it
+    connect_regions(&mut map, (start_x, start_y));
+
+    // doors go where the tunnels run into the rooms. Locked ones come with a keycard
+    // somewhere the player can get to without one
+    if walled && place_doors(&rooms, &mut map, rng) {
+        place_keycard(&map, objects, rng, templates);
+    }
+
+    // and one oxygen station somewhere on every level, out of the way of the stairs
+    let room = rooms[rng.gen_range(0, rooms.len())];
+    let x = rng.gen_range(room.x1 + 1, room.x2);
+    let y = rng.gen_range(room.y1 + 1, room.y2);
+    if map[x as usize][y as usize].kind == TileKind::Floor {
+        map[x as usize][y as usize] = Tile::oxygen_station();
+    }
+
+    // deeper down, the hull has taken hits. A breach vents the room it's in, and whatever
+    // gets opened up to it later. Caves and wreckage have no rooms to seal off, so they don't get one
+    let breach_chance = (BREACH_CHANCE_PER_DEPTH * (depth - 1) as f32).min(MAX_BREACH_CHANCE);
+    if walled && rng.gen::<f32>() < breach_chance {
+        for _ in 0..BREACH_TRIES {
+            let room = rooms[rng.gen_range(0, rooms.len())];
+            let x = rng.gen_range(room.x1 + 1, room.x2);
+            let y = rng.gen_range(room.y1 + 1, room.y2);
+            if map[x as usize][y as usize].kind != TileKind::Floor {
+                continue;
+            }
+            if let Some(breached) = breach_room(room, x, y, &map) {
+                map = breached;
+                break;
             }
-            rooms.push(new_room);
         }
     }
 
     map
 }
 
+/// put doors in some of the gaps the tunnels left in the room walls.
+/// Returns whether any of them are locked
+fn place_doors(rooms: &[Rect], map: &mut Map, rng: &mut GameRng) -> bool {
+    let mut any_locked = false;
+    for room in rooms {
+        for x in room.x1..=room.x2 {
+            for y in room.y1..=room.y2 {
+                let on_wall = x == room.x1 || x == room.x2 || y == room.y1 || y == room.y2;
+                if !on_wall || !is_doorway(x, y, map) {
+                    continue;
+                }
+                let roll = rng.gen::<f32>();
+                let kind = if roll < LOCKED_DOOR_CHANCE {
+                    TileKind::LockedDoor
+                } else if roll < LOCKED_DOOR_CHANCE + AIRLOCK_CHANCE {
+                    TileKind::Airlock
+                } else if roll < LOCKED_DOOR_CHANCE + AIRLOCK_CHANCE + DOOR_CHANCE {
+                    TileKind::Door
+                } else {
+                    continue;
+                };
+                any_locked |= kind == TileKind::LockedDoor;
+                map[x as usize][y as usize] = Tile::door(kind);
+            }
+        }
+    }
+    any_locked
+}
+
+/// the map with a hull breach at (x, y) and a door shut in every open gap in the room's
+/// walls, so only the room loses its air. `None` if the air gets out anyway, e.g. where
+/// a tunnel runs along the wall
+fn breach_room(room: Rect, x: i32, y: i32, map: &Map) -> Option<Map> {
+    let mut map = map.clone();
+    map[x as usize][y as usize] = Tile::hull_breach();
+    for wall_x in room.x1..=room.x2 {
+        for wall_y in room.y1..=room.y2 {
+            let on_wall = wall_x == room.x1 || wall_x == room.x2 || wall_y == room.y1 || wall_y == room.y2;
+            if on_wall && is_doorway(wall_x, wall_y, &map) {
+                map[wall_x as usize][wall_y as usize] = Tile::door(TileKind::Door);
+            }
+        }
+    }
+    vent_breaches(&mut map);
+    let inside = |x: i32, y: i32| x > room.x1 && x < room.x2 && y > room.y1 && y < room.y2;
+    let leaked = map.iter().enumerate().any(|(x, column)| {
+        column
+            .iter()
+            .enumerate()
+            .any(|(y, tile)| !tile.blocked && !tile.pressurized && !inside(x as i32, y as i32))
+    });
+    if leaked {
+        None
+    } else {
+        Some(map)
+    }
+}
+
+/// whether (x, y) is a gap in a wall: floor with walls on two opposite sides,
+/// and open on the other two
+pub fn is_doorway(x: i32, y: i32, map: &Map) -> bool {
+    let wall = |x: i32, y: i32| {
+        x < 0
+            || y < 0
+            || x as usize >= map.len()
+            || y as usize >= map[0].len()
+            || map[x as usize][y as usize].kind == TileKind::Wall
+    };
+    if wall(x, y) || map[x as usize][y as usize].kind != TileKind::Floor {
+        return false;
+    }
+    let walls_left_right = wall(x - 1, y) && wall(x + 1, y);
+    let walls_above_below = wall(x, y - 1) && wall(x, y + 1);
+    (walls_left_right && !wall(x, y - 1) && !wall(x, y + 1))
+        || (walls_above_below && !wall(x - 1, y) && !wall(x + 1, y))
+}
+
+/// drop a keycard on a floor tile the player can reach without getting through a
+/// locked door first
+fn place_keycard(map: &Map, objects: &mut Vec<Object>, rng: &mut GameRng, templates: &Templates) {
+    let passable = |x: i32, y: i32| {
+        let tile = &map[x as usize][y as usize];
+        !tile.blocked || (tile.is_door() && tile.kind != TileKind::LockedDoor)
+    };
+    let mut reached = vec![vec![false; map[0].len()]; map.len()];
+    let mut frontier = vec![objects[PLAYER].pos()];
+    let mut floors = vec![];
+    while let Some((x, y)) = frontier.pop() {
+        if reached[x as usize][y as usize] || !passable(x, y) {
+            continue;
+        }
+        reached[x as usize][y as usize] = true;
+        if map[x as usize][y as usize].kind == TileKind::Floor {
+            floors.push((x, y));
+        }
+        // the map is walled in all around, so the neighbours are never off it
+        frontier.extend(NEIGHBOURS.iter().map(|&(dx, dy)| (x + dx, y + dy)));
+    }
+    if let Some(keycard) = templates.item(Item::Keycard) {
+        if !floors.is_empty() {
+            let (x, y) = floors[rng.gen_range(0, floors.len())];
+            objects.push(keycard.spawn(x, y));
+        }
+    }
+}
+
+/// find the first tile of the given kind, e.g. to put the player on a staircase
+pub fn find_tile(map: &Map, kind: TileKind) -> Option<(i32, i32)> {
+    map.iter().enumerate().find_map(|(x, column)| {
+        column
+            .iter()
+            .position(|tile| tile.kind == kind)
+            .map(|y| (x as i32, y as i32))
+    })
+}
+
 // room part
 /// A rectangle on the map, used to characterise a room.
 #[derive(Clone, Copy, Debug)]
 pub struct Rect {
-    x1: i32,
-    y1: i32,
-    x2: i32,
-    y2: i32,
+    pub x1: i32,
+    pub y1: i32,
+    pub x2: i32,
+    pub y2: i32,
 }
 
 impl Rect {
@@ -118,46 +285,53 @@ pub fn create_room(room: Rect, map: &mut Map) {
     }
 }
 
-fn place_objects(room: Rect, map: &Map, objects: &mut Vec<Object>) {
+fn place_objects(
+    room: Rect,
+    map: &Map,
+    objects: &mut Vec<Object>,
+    rng: &mut GameRng,
+    templates: &Templates,
+    depth: u32,
+) {
+    // what can show up this deep
+    let monster_chances = templates.monster_chances(depth);
+    let item_chances = templates.item_chances(depth);
+
     // choose random number of monsters
-    let num_monsters = rand::thread_rng().gen_range(0, MAX_ROOM_MONSTERS + 1);
+    let max_monsters = from_dungeon_level(MAX_ROOM_MONSTERS, depth);
+    let num_monsters = if monster_chances.is_empty() {
+        0
+    } else {
+        rng.gen_range(0, max_monsters + 1)
+    };
 
     for _ in 0..num_monsters {
         // choose random spot for this monster
-        let x = rand::thread_rng().gen_range(room.x1 + 1, room.x2);
-        let y = rand::thread_rng().gen_range(room.y1 + 1, room.y2);
+        let x = rng.gen_range(room.x1 + 1, room.x2);
+        let y = rng.gen_range(room.y1 + 1, room.y2);
         if !is_blocked(x, y, map, objects) {
-            let mut monster = if rand::random::<f32>() < 0.8 {
-                // 80% chance of getting an orc
-                // create a husk
-                let mut fish = Object::new(x, y, 'f', "Fish", AZURE, true);
-                fish.fighter = Some(Fighter {
-                    max_hp: 10,
-                    hp: 10,
-                    defense: 0,
-                    power: 3,
-                    on_death: DeathCallback::Monster,
-                });
-                fish.attack_message = "chomps".into();
-                fish.ai = Some(Ai::Basic);
-                fish
-            } else {
-                // create a husk
-                let mut husk = Object::new(x, y, 'H', "Husk", BRASS, true);
-                husk.fighter = Some(Fighter {
-                    max_hp: 16,
-                    hp: 16,
-                    defense: 1,
-                    power: 4,
-                    on_death: DeathCallback::Monster,
-                });
-                husk.attack_message = "scratches".into();
-                husk.ai = Some(Ai::Basic);
-                husk
-            };
-
-            monster.alive = true;
-            objects.push(monster);
+            let template = &templates.monsters[random_choice(&monster_chances, rng)];
+            objects.push(template.spawn(x, y));
+        }
+    }
+
+    // choose random number of items
+    let max_items = from_dungeon_level(MAX_ROOM_ITEMS, depth);
+    let num_items = if item_chances.is_empty() {
+        0
+    } else {
+        rng.gen_range(0, max_items + 1)
+    };
+
+    for _ in 0..num_items {
+        // choose random spot for this item
+        let x = rng.gen_range(room.x1 + 1, room.x2);
+        let y = rng.gen_range(room.y1 + 1, room.y2);
+
+        // only place it if the tile is not blocked
+        if !is_blocked(x, y, map, objects) {
+            let template = &templates.items[random_choice(&item_chances, rng)];
+            objects.push(template.spawn(x, y));
         }
     }
 }
@@ -174,4 +348,35 @@ pub fn create_v_tunnel(y1: i32, y2: i32, x: i32, map: &mut Map) {
     for y in cmp::min(y1, y2)..(cmp::max(y1, y2) + 1) {
         map[x as usize][y as usize] = Tile::empty();
     }
-}
\ No newline at end of file
+}
+
+// spawn chances part
+/// pick one of the options at random, each weighted by its chance
+pub fn random_choice<T: Copy>(choices: &[(T, u32)], rng: &mut GameRng) -> T {
+    let total: u32 = choices.iter().map(|&(_, chance)| chance).sum();
+    let mut dice = rng.gen_range(0, total);
+    for &(choice, chance) in choices {
+        if dice < chance {
+            return choice;
+        }
+        dice -= chance;
+    }
+    unreachable!("the dice can't roll past the total of the chances")
+}
+
+/// a value that kicks in from dungeon level `level` on, until the next one takes over
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Transition {
+    pub level: u32,
+    pub value: u32,
+}
+
+/// look up what the table says for dungeon level `level`: the value of the deepest
+/// transition it has reached, or 0 if it hasn't reached any yet
+pub fn from_dungeon_level(table: &[Transition], level: u32) -> u32 {
+    table
+        .iter()
+        .rev()
+        .find(|transition| level >= transition.level)
+        .map_or(0, |transition| transition.value)
+}
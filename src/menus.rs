@@ -0,0 +1,179 @@
+use tcod::console::*;
+use tcod::colors::*;
+
+use aeros::game::*;
+use aeros::object::Object;
+use aeros::structs::Game;
+
+use crate::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+const INVENTORY_WIDTH: i32 = 50;
+const LEVEL_SCREEN_WIDTH: i32 = 40;
+const CHARACTER_SCREEN_WIDTH: i32 = 30;
+
+/// show a list of lettered options over whatever is on screen and wait for a choice.
+/// Returns the index of the chosen option, or `None` for any other key
+pub fn menu<T: AsRef<str>>(header: &str, options: &[T], width: i32, root: &mut Root) -> Option<usize> {
+    assert!(
+        options.len() <= 26,
+        "Cannot have a menu with more than 26 options."
+    );
+
+    // calculate total height for the header (after auto-wrap) and one line per option
+    let header_height = if header.is_empty() {
+        0
+    } else {
+        root.get_height_rect(0, 0, width, SCREEN_HEIGHT, header)
+    };
+    let height = options.len() as i32 + header_height;
+
+    // create an off-screen console that represents the menu's window
+    let mut window = Offscreen::new(width, height);
+
+    // print the header, with auto-wrap
+    window.set_default_foreground(WHITE);
+    window.print_rect_ex(
+        0,
+        0,
+        width,
+        height,
+        BackgroundFlag::None,
+        TextAlignment::Left,
+        header,
+    );
+
+    // print all the options
+    for (index, option_text) in options.iter().enumerate() {
+        let menu_letter = (b'a' + index as u8) as char;
+        let text = format!("({}) {}", menu_letter, option_text.as_ref());
+        window.print_ex(
+            0,
+            header_height + index as i32,
+            BackgroundFlag::None,
+            TextAlignment::Left,
+            text,
+        );
+    }
+
+    // blit the contents of "window" to the root console, centered
+    let x = SCREEN_WIDTH / 2 - width / 2;
+    let y = SCREEN_HEIGHT / 2 - height / 2;
+    blit(&window, (0, 0), (width, height), root, (x, y), 1.0, 0.7);
+
+    // present the root console to the player and wait for a key-press
+    root.flush();
+    let key = root.wait_for_keypress(true);
+
+    // convert the ASCII code to an index; if it corresponds to an option, return it
+    if key.printable.is_alphabetic() {
+        let index = key.printable.to_ascii_lowercase() as usize - 'a' as usize;
+        if index < options.len() {
+            Some(index)
+        } else {
+            None
+        }
+    } else {
+        None
+    }
+}
+
+/// list the inventory and let the player pick an item
+pub fn inventory_menu(inventory: &[Object], header: &str, root: &mut Root) -> Option<usize> {
+    // show a menu with each item of the inventory as an option
+    let options = if inventory.is_empty() {
+        vec!["Inventory is empty.".into()]
+    } else {
+        inventory
+            .iter()
+            .map(|item| {
+                // show additional information, in case it's equipped
+                match item.equipment {
+                    Some(equipment) if equipment.equipped => {
+                        format!("{} (on {})", item.name, equipment.slot)
+                    }
+                    _ => item.name.clone(),
+                }
+            })
+            .collect()
+    };
+
+    let inventory_index = menu(header, &options, INVENTORY_WIDTH, root);
+
+    // if an item was chosen, return it
+    if !inventory.is_empty() {
+        inventory_index
+    } else {
+        None
+    }
+}
+
+/// ask the player which stat to raise until they pick one
+pub fn level_up_menu(player: &Object, root: &mut Root) -> LevelUpChoice {
+    let fighter = player.fighter.as_ref().unwrap();
+    let mut choice = None;
+    while choice.is_none() {
+        // keep asking until a choice is made
+        choice = menu(
+            &format!(
+                "Your battle skills grow stronger! You reached level {}!\nChoose a stat to raise:\n",
+                player.level + 1
+            ),
+            &[
+                format!("Constitution (+20 HP, from {})", fighter.base_max_hp),
+                format!("Strength (+1 attack, from {})", fighter.base_power),
+                format!("Agility (+1 defense, from {})", fighter.base_defense),
+            ],
+            LEVEL_SCREEN_WIDTH,
+            root,
+        );
+    }
+    match choice {
+        Some(0) => LevelUpChoice::Constitution,
+        Some(1) => LevelUpChoice::Strength,
+        _ => LevelUpChoice::Agility,
+    }
+}
+
+/// show the player's level, experience and current stats
+pub fn character_screen(game: &Game, player: &Object, root: &mut Root) {
+    if let Some(fighter) = player.fighter.as_ref() {
+        let level_up_xp = level_up_xp(player.level);
+        let msg = format!(
+            "Character information
+
+Level: {}
+Experience: {}
+Experience to level up: {}
+
+Maximum HP: {}
+Attack: {}
+Defense: {}",
+            player.level,
+            fighter.xp,
+            level_up_xp - fighter.xp,
+            player.max_hp(game),
+            player.power(game),
+            player.defense(game),
+        );
+        msgbox(&msg, CHARACTER_SCREEN_WIDTH, root);
+    }
+}
+
+/// a menu with no options, just to tell the player something
+pub fn msgbox(text: &str, width: i32, root: &mut Root) {
+    let options: &[&str] = &[];
+    menu(text, options, width, root);
+}
+
+/// sum up the run after the player died and wait for a key
+pub fn death_screen(game: &Game, root: &mut Root) {
+    let cause = match &game.cause_of_death {
+        Some(cause) => format!("You were {}", cause),
+        None => "You died".into(),
+    };
+    let text = format!(
+        "\n{} on dungeon level {}.\n\nYou survived {} turns and killed {} monsters.\n\nPress any key to return to the main menu.\n",
+        cause, game.level, game.turn, game.kills
+    );
+    msgbox(&text, 40, root);
+}
@@ -1,22 +1,32 @@
 use crate::structs::*;
-use tcod::console::*;
-use tcod::colors::*;
+use crate::ai::{flee_if_badly_hurt, is_badly_hurt};
+use crate::colors::*;
 use crate::maps::*;
+use crate::items::*;
+use crate::doors::open_door;
+use crate::effects::*;
+use crate::ranged::RangedWeapon;
+use crate::survival::*;
+use serde::{Deserialize, Serialize};
 use std::cmp;
 
 const PLAYER: usize = 0;
 
 // combat-related properties and methods (monster, player, NPC).
-#[derive(Clone, Copy, Debug, PartialEq)]
+/// The `base_` stats are the fighter's own, see `Object::power` and friends for the
+/// effective ones that include equipment. `xp` is what a monster is worth when
+/// killed, and what the player has earned so far.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Fighter {
-    pub max_hp: i32,
+    pub base_max_hp: i32,
     pub hp: i32,
-    pub defense: i32,
-    pub power: i32,
+    pub base_defense: i32,
+    pub base_power: i32,
+    pub xp: i32,
     pub on_death: DeathCallback
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum DeathCallback {
     Player,
     Monster,
@@ -33,11 +43,48 @@ impl DeathCallback {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Ai {
-    Basic,
+    /// chases the player while it can see them. Once they're out of sight it heads
+    /// for where it `last_seen` them, then searches around for `search_turns`
+    Basic {
+        last_seen: Option<(i32, i32)>,
+        search_turns: i32,
+    },
+    /// stumbles around at random for a number of turns, then goes back to `previous_ai`
+    Confused {
+        previous_ai: Box<Ai>,
+        turns: i32,
+    },
+    /// badly hurt, runs from the player for a number of turns, or until it recovers,
+    /// then goes back to `previous_ai`
+    Fleeing {
+        previous_ai: Box<Ai>,
+        turns: i32,
+    },
+    /// never moves, only attacks what comes close. Turrets and the like
+    Stationary,
+    /// shoots the player from a distance with its ranged weapon, backing off when they
+    /// close in. Tracks where it `last_seen` them like `Basic`
+    Ranged { last_seen: Option<(i32, i32)> },
 }
 
+impl Ai {
+    /// a basic AI that hasn't seen the player yet
+    pub fn basic() -> Self {
+        Ai::Basic {
+            last_seen: None,
+            search_turns: 0,
+        }
+    }
+
+    /// a ranged AI that hasn't seen the player yet
+    pub fn ranged() -> Self {
+        Ai::Ranged { last_seen: None }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Object {
     pub x: i32,
     pub y: i32,
@@ -46,9 +93,22 @@ pub struct Object {
     pub name: String,
     pub blocks: bool,
     pub alive: bool,
+    /// character level, only the player ever goes past 1
+    pub level: i32,
     pub attack_message: String,
     pub fighter: Option<Fighter>,  
-    pub ai: Option<Ai>
+    pub ai: Option<Ai>,
+    pub item: Option<Item>,
+    pub equipment: Option<Equipment>,
+    pub ranged: Option<RangedWeapon>,
+    /// what's left of a hardsuit's seals
+    pub integrity: Option<Integrity>,
+    /// temporary conditions, see `effects::tick_effects`
+    pub effects: Vec<StatusEffect>,
+    /// what its attacks leave the target with, on top of the damage
+    pub inflicts: Option<StatusEffect>,
+    /// suffocates in vacuum. The player has their tanks instead, see `survival::breathe`
+    pub needs_air: bool,
 }
 
 fn player_death(player: &mut Object, game: &mut Game) {
@@ -93,11 +153,19 @@ impl Object {
             char,
             color,
             name: name.into(),
-            blocks: blocks,
+            blocks,
             alive: false,
+            level: 1,
             attack_message: "attacks".into(),
             fighter: None,
             ai: None,
+            item: None,
+            equipment: None,
+            ranged: None,
+            integrity: None,
+            effects: vec![],
+            inflicts: None,
+            needs_air: false,
         }
     }
 
@@ -112,7 +180,10 @@ impl Object {
         (self.x, self.y)
     }
 
-    pub fn take_damage(&mut self, damage: i32, game: &mut Game) {
+    /// take a hit, dying if it was too much. Returns what it was as a fighter if this
+    /// killed it, for whoever did it to `record_kill`
+    pub fn take_damage(&mut self, damage: i32, game: &mut Game) -> Option<Fighter> {
+        let was_badly_hurt = is_badly_hurt(self);
         // apply damage if possible
         if let Some(fighter) = self.fighter.as_mut() {
             if damage > 0 {
@@ -123,36 +194,131 @@ impl Object {
             }
         }
         // check for death, call the death function
+        let mut killed = None;
         if let Some(fighter) = self.fighter {
             if fighter.hp <= 0 {
+                // the death callback strips monsters of their fighter, so keep it now
+                if self.alive {
+                    killed = Some(fighter);
+                }
                 self.alive = false;
                 fighter.on_death.callback(self, game);
             }
         }
+        flee_if_badly_hurt(self, was_badly_hurt, game);
+        killed
+    }
+
+    /// heal by the given amount, without going over the maximum
+    pub fn heal(&mut self, amount: i32, game: &Game) {
+        let max_hp = self.max_hp(game);
+        if let Some(ref mut fighter) = self.fighter {
+            fighter.hp += amount;
+            if fighter.hp > max_hp {
+                fighter.hp = max_hp;
+            }
+        }
+    }
+
+    /// attack power, including bonuses from equipped gear
+    pub fn power(&self, game: &Game) -> i32 {
+        let base_power = self.fighter.map_or(0, |f| f.base_power);
+        let bonus: i32 = self
+            .get_all_equipped(game)
+            .iter()
+            .map(|e| e.power_bonus)
+            .sum();
+        base_power + bonus
+    }
+
+    /// defense, including bonuses from equipped gear
+    pub fn defense(&self, game: &Game) -> i32 {
+        let base_defense = self.fighter.map_or(0, |f| f.base_defense);
+        let bonus: i32 = self
+            .get_all_equipped(game)
+            .iter()
+            .map(|e| e.defense_bonus)
+            .sum();
+        base_defense + bonus
+    }
+
+    /// maximum HP, including bonuses from equipped gear
+    pub fn max_hp(&self, game: &Game) -> i32 {
+        let base_max_hp = self.fighter.map_or(0, |f| f.base_max_hp);
+        let bonus: i32 = self
+            .get_all_equipped(game)
+            .iter()
+            .map(|e| e.max_hp_bonus)
+            .sum();
+        base_max_hp + bonus
+    }
+
+    /// returns a list of equipped items. Only the player carries gear
+    pub fn get_all_equipped(&self, game: &Game) -> Vec<Equipment> {
+        if self.is_player() {
+            game.inventory
+                .iter()
+                .filter(|item| item.equipment.is_some_and(|e| e.equipped))
+                .map(|item| item.equipment.unwrap())
+                .collect()
+        } else {
+            vec![] // other objects have no equipment
+        }
+    }
+
+    /// whether this is the player, going by how it dies
+    pub fn is_player(&self) -> bool {
+        self.fighter.is_some_and(|f| f.on_death == DeathCallback::Player)
     }
 
     pub fn attack(&mut self, target: &mut Object, game: &mut Game) {
+        let power = self.power(game);
+        let verb = self.attack_message.clone();
+        self.strike(target, power, &verb, game);
+    }
+
+    /// hit the target with an attack of the given power, e.g. a melee attack or a shot
+    pub fn strike(&mut self, target: &mut Object, power: i32, verb: &str, game: &mut Game) {
         // a simple formula for attack damage
-        let damage = self.fighter.map_or(0, |f| f.power) - target.fighter.map_or(0, |f| f.defense);
+        let damage = power - target.defense(game);
         if damage > 0 {
             // make the target take some damage
             game.messages.add(format!(
                 "{} {} {}, dealing {} damage!",
-                self.name, self.attack_message, target.name.to_lowercase(), damage
+                self.name, verb, target.name.to_lowercase(), damage
             ), DESATURATED_GREEN);
-            target.take_damage(damage, game);
+            if target.is_player() {
+                // every hit that gets through leaves a mark on the suit
+                damage_suit(damage, game);
+            }
+            if let Some(victim) = target.take_damage(damage, game) {
+                self.record_kill(victim, game);
+            } else if let Some(effect) = self.inflicts {
+                let effect = StatusEffect {
+                    by_player: self.is_player(),
+                    ..effect
+                };
+                apply_effect(target, effect, game);
+            }
         } else {
             game.messages.add(format!(
                 "{} {} {} but it has no effect!",
-                self.name, self.attack_message, target.name.to_lowercase()
+                self.name, verb, target.name.to_lowercase()
             ), DESATURATED_GREEN);
         }
     }
 
-    /// set the color and then draw the character that represents this object at its position
-    pub fn draw(&self, con: &mut dyn Console) {
-        con.set_default_foreground(self.color);
-        con.put_char(self.x, self.y, self.char, BackgroundFlag::None);
+    /// keep score after this object killed something: it earns the victim's experience,
+    /// and the game counts the player's kills or remembers what killed the player
+    pub fn record_kill(&mut self, victim: Fighter, game: &mut Game) {
+        if let Some(fighter) = self.fighter.as_mut() {
+            fighter.xp += victim.xp;
+        }
+        if victim.on_death == DeathCallback::Player {
+            game.cause_of_death = Some(format!("killed by a {}", self.name.to_lowercase()));
+        } else if self.is_player() {
+            game.kills += 1;
+        }
     }
 
     /// return the distance to another object
@@ -193,12 +359,16 @@ pub fn player_move_or_attack(dx: i32, dy: i32, game: &mut Game, objects: &mut [O
     .iter()
     .position(|object| object.fighter.is_some() && object.pos() == (x, y));
 
-    // attack if target found, move otherwise
+    // attack if target found, open closed doors by walking into them, move otherwise
+    let closed_door = game.map[x as usize][y as usize].is_door() && game.map[x as usize][y as usize].blocked;
     match target_id {
         Some(target_id) => {
             let (player, target) = mut_two(PLAYER, target_id, objects);
             player.attack(target, game);
         }
+        None if closed_door => {
+            open_door(x, y, game);
+        }
         None => {
             move_by(PLAYER, dx, dy, &game.map, objects);
         }
@@ -0,0 +1,134 @@
+use crate::maps::*;
+use crate::object::*;
+use crate::tile::{Tile, TileKind};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+
+// step costs, scaled by 10 so diagonals can cost ~sqrt(2) in integers
+const STRAIGHT_COST: i32 = 10;
+const DIAGONAL_COST: i32 = 14;
+// walking through a tile with someone in it. Expensive enough to go around
+// them when there is a reasonable detour, but they might still move out of the way
+const OCCUPIED_COST: i32 = 100;
+
+/// octile distance, the exact cost between two points on an open map
+fn heuristic((x1, y1): (i32, i32), (x2, y2): (i32, i32)) -> i32 {
+    let dx = (x1 - x2).abs();
+    let dy = (y1 - y2).abs();
+    STRAIGHT_COST * (dx + dy) + (DIAGONAL_COST - 2 * STRAIGHT_COST) * dx.min(dy)
+}
+
+/// find the cheapest path from `start` to `goal` with A*, moving in 8 directions over
+/// unblocked tiles. Tiles with blocking objects in them are costly rather than impassable.
+/// Returns the steps after `start`, ending at `goal`, or `None` if walls cut it off
+pub fn find_path(
+    start: (i32, i32),
+    goal: (i32, i32),
+    map: &Map,
+    objects: &[Object],
+) -> Option<Vec<(i32, i32)>> {
+    let width = map.len() as i32;
+    let height = map.first().map_or(0, |column| column.len()) as i32;
+    let index = |(x, y): (i32, i32)| (y * width + x) as usize;
+    let in_bounds = |(x, y): (i32, i32)| x >= 0 && y >= 0 && x < width && y < height;
+    if !in_bounds(start) || !in_bounds(goal) {
+        return None;
+    }
+
+    let occupied: HashSet<(i32, i32)> = objects
+        .iter()
+        .filter(|object| object.blocks)
+        .map(|object| object.pos())
+        .filter(|&pos| pos != start && pos != goal)
+        .collect();
+
+    let size = (width * height) as usize;
+    let mut cost_so_far = vec![i32::MAX; size];
+    let mut came_from = vec![None; size];
+    let mut frontier = BinaryHeap::new();
+
+    cost_so_far[index(start)] = 0;
+    frontier.push(Reverse((heuristic(start, goal), 0, start)));
+
+    while let Some(Reverse((_, cost, current))) = frontier.pop() {
+        if current == goal {
+            // walk back from the goal to get the steps in order
+            let mut path = vec![];
+            let mut step = goal;
+            while step != start {
+                path.push(step);
+                step = came_from[index(step)]?;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if cost > cost_so_far[index(current)] {
+            // a cheaper way here was already expanded
+            continue;
+        }
+
+        for &(dx, dy) in NEIGHBOURS.iter() {
+            let next = (current.0 + dx, current.1 + dy);
+            if !in_bounds(next) || map[next.0 as usize][next.1 as usize].blocked {
+                continue;
+            }
+            let mut step_cost = if dx != 0 && dy != 0 {
+                DIAGONAL_COST
+            } else {
+                STRAIGHT_COST
+            };
+            if occupied.contains(&next) {
+                step_cost += OCCUPIED_COST;
+            }
+            let new_cost = cost + step_cost;
+            if new_cost < cost_so_far[index(next)] {
+                cost_so_far[index(next)] = new_cost;
+                came_from[index(next)] = Some(current);
+                frontier.push(Reverse((new_cost + heuristic(next, goal), new_cost, next)));
+            }
+        }
+    }
+    None
+}
+
+/// the way for the player to travel to `goal` over tiles they have already seen, so the
+/// path doesn't give away anything they don't know. Doors they can open are on the way,
+/// and locked ones too if they're carrying a keycard. Monsters are left out of it, travel
+/// stops as soon as one shows up anyway
+pub fn travel_path(
+    start: (i32, i32),
+    goal: (i32, i32),
+    map: &Map,
+    has_keycard: bool,
+) -> Option<Vec<(i32, i32)>> {
+    let known: Map = map
+        .iter()
+        .map(|column| {
+            column
+                .iter()
+                .map(|&tile| {
+                    let openable = tile.is_door() && (has_keycard || tile.kind != TileKind::LockedDoor);
+                    Tile {
+                        blocked: (tile.blocked && !openable) || !tile.explored,
+                        ..tile
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    find_path(start, goal, &known, &[])
+}
+
+/// take one step along the best path towards the target. Falls back to heading
+/// straight for it when there is no path at all
+pub fn move_astar(id: usize, target_x: i32, target_y: i32, map: &Map, objects: &mut [Object]) {
+    match find_path(objects[id].pos(), (target_x, target_y), map, objects) {
+        Some(path) => {
+            if let Some(&(x, y)) = path.first() {
+                // if someone is standing in the way, wait for them to move
+                move_by(id, x - objects[id].x, y - objects[id].y, map, objects);
+            }
+        }
+        None => move_towards(id, target_x, target_y, map, objects),
+    }
+}
@@ -0,0 +1,204 @@
+use crate::maps::*;
+use crate::object::*;
+use crate::rng::GameRng;
+use crate::templates::Templates;
+use crate::tile::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+
+// how many prefabs a level can get, and the chance of each one showing up
+const MAX_PREFABS: i32 = 2;
+const PREFAB_CHANCE: f32 = 0.5;
+// how many random spots to try before giving up on fitting a prefab in
+const PLACEMENT_TRIES: i32 = 100;
+// everything that can be drawn in a prefab's rows
+const MARKERS: &str = " #.+&MI";
+
+/// a hand-drawn room. In `rows`, `#` is wall, `.` floor, `+` a door, `&` an oxygen
+/// station, `M` and `I` a monster or an item on the floor, and a space leaves the rock as it is
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Prefab {
+    pub name: String,
+    pub rows: Vec<String>,
+}
+
+impl Prefab {
+    /// make sure every marker in the rows means something, so a typo in the data file
+    /// shows up when it's loaded instead of quietly turning into floor
+    pub fn check(&self) -> Result<(), String> {
+        for (y, row) in self.rows.iter().enumerate() {
+            if let Some(marker) = row.chars().find(|&c| !MARKERS.contains(c)) {
+                return Err(format!("prefab \"{}\", row {}: unknown marker '{}'", self.name, y + 1, marker));
+            }
+        }
+        Ok(())
+    }
+
+    /// the rows as a grid of columns, so `grid[x][y]` like the map. Short rows are
+    /// padded out with rock
+    fn grid(&self) -> Vec<Vec<char>> {
+        let width = self.rows.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+        (0..width)
+            .map(|x| {
+                self.rows
+                    .iter()
+                    .map(|row| row.chars().nth(x).unwrap_or(' '))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// turn the grid a quarter clockwise
+fn rotate(grid: &[Vec<char>]) -> Vec<Vec<char>> {
+    let width = grid.len();
+    let height = grid.first().map_or(0, |column| column.len());
+    (0..height)
+        .map(|x| (0..width).map(|y| grid[y][height - 1 - x]).collect())
+        .collect()
+}
+
+/// flip the grid left to right
+fn mirror(grid: &[Vec<char>]) -> Vec<Vec<char>> {
+    grid.iter().rev().cloned().collect()
+}
+
+/// stamp a few of the prefabs into solid rock on the map, each turned and flipped at
+/// random, fill them with what their markers ask for and dig tunnels from their doors
+/// to the rest of the level
+pub fn place_prefabs(map: &mut Map, objects: &mut Vec<Object>, rng: &mut GameRng, templates: &Templates, depth: u32) {
+    if templates.prefabs.is_empty() {
+        return;
+    }
+    let mut taken = HashSet::new();
+    for _ in 0..MAX_PREFABS {
+        if rng.gen::<f32>() >= PREFAB_CHANCE {
+            continue;
+        }
+        let prefab = &templates.prefabs[rng.gen_range(0, templates.prefabs.len())];
+        let mut grid = prefab.grid();
+        for _ in 0..rng.gen_range(0, 4) {
+            grid = rotate(&grid);
+        }
+        if rng.gen() {
+            grid = mirror(&grid);
+        }
+        if let Some((x, y)) = find_space(&grid, map, rng) {
+            stamp(&grid, x, y, map, objects, rng, templates, depth, &mut taken);
+        }
+    }
+}
+
+/// a random spot where the grid fits into solid rock with a wall's width to spare all around
+fn find_space(grid: &[Vec<char>], map: &Map, rng: &mut GameRng) -> Option<(i32, i32)> {
+    let width = grid.len() as i32;
+    let height = grid.first().map_or(0, |column| column.len()) as i32;
+    let map_width = map.len() as i32;
+    let map_height = map[0].len() as i32;
+    // the spare wall can't be the edge of the map either, or a door there couldn't be dug out
+    if width + 4 >= map_width || height + 4 >= map_height {
+        return None;
+    }
+    (0..PLACEMENT_TRIES)
+        .map(|_| {
+            (
+                rng.gen_range(2, map_width - width - 1),
+                rng.gen_range(2, map_height - height - 1),
+            )
+        })
+        .find(|&(x, y)| {
+            (x - 1..=x + width).all(|map_x| {
+                (y - 1..=y + height).all(|map_y| map[map_x as usize][map_y as usize].kind == TileKind::Wall)
+            })
+        })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn stamp(
+    grid: &[Vec<char>],
+    left: i32,
+    top: i32,
+    map: &mut Map,
+    objects: &mut Vec<Object>,
+    rng: &mut GameRng,
+    templates: &Templates,
+    depth: u32,
+    taken: &mut HashSet<(i32, i32)>,
+) {
+    let monster_chances = templates.monster_chances(depth);
+    let item_chances = templates.item_chances(depth);
+    let mut doors = vec![];
+    for (dx, column) in grid.iter().enumerate() {
+        for (dy, &marker) in column.iter().enumerate() {
+            let (x, y) = (left + dx as i32, top + dy as i32);
+            let tile = match marker {
+                ' ' => continue,
+                '#' => Tile::wall(),
+                '+' => {
+                    doors.push((x, y));
+                    Tile::door(TileKind::Door)
+                }
+                '&' => Tile::oxygen_station(),
+                '.' | 'M' | 'I' => Tile::empty(),
+                // `check` turns these away when the templates are loaded
+                _ => continue,
+            };
+            map[x as usize][y as usize] = tile;
+            taken.insert((x, y));
+            match marker {
+                'M' if !monster_chances.is_empty() => {
+                    let template = &templates.monsters[random_choice(&monster_chances, rng)];
+                    objects.push(template.spawn(x, y));
+                }
+                'I' if !item_chances.is_empty() => {
+                    let template = &templates.items[random_choice(&item_chances, rng)];
+                    objects.push(template.spawn(x, y));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // every door gets a tunnel out to the rest of the level
+    for (x, y) in doors {
+        for (out_x, out_y) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+            if !taken.contains(&(out_x, out_y)) {
+                dig_out(out_x, out_y, map, taken);
+            }
+        }
+    }
+}
+
+/// dig the shortest way from (x, y) to the nearest open floor, going around the prefabs
+fn dig_out(x: i32, y: i32, map: &mut Map, taken: &HashSet<(i32, i32)>) {
+    let width = map.len() as i32;
+    let height = map[0].len() as i32;
+    let mut came_from = vec![vec![None; height as usize]; width as usize];
+    let mut frontier = VecDeque::new();
+    came_from[x as usize][y as usize] = Some((x, y));
+    frontier.push_back((x, y));
+    while let Some((cx, cy)) = frontier.pop_front() {
+        if !map[cx as usize][cy as usize].blocked {
+            // found the way out, dig back along the path to the door
+            let mut step = (cx, cy);
+            while step != (x, y) {
+                step = came_from[step.0 as usize][step.1 as usize].unwrap_or((x, y));
+                if map[step.0 as usize][step.1 as usize].kind == TileKind::Wall {
+                    map[step.0 as usize][step.1 as usize] = Tile::empty();
+                }
+            }
+            return;
+        }
+        for (nx, ny) in [(cx - 1, cy), (cx + 1, cy), (cx, cy - 1), (cx, cy + 1)] {
+            // stay off the edge of the map and out of the prefabs
+            if nx < 1 || ny < 1 || nx >= width - 1 || ny >= height - 1 || taken.contains(&(nx, ny)) {
+                continue;
+            }
+            if came_from[nx as usize][ny as usize].is_none() {
+                came_from[nx as usize][ny as usize] = Some((cx, cy));
+                frontier.push_back((nx, ny));
+            }
+        }
+    }
+}
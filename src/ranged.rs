@@ -0,0 +1,114 @@
+use crate::colors::*;
+use crate::los::*;
+use crate::object::*;
+use crate::structs::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+// chance to hit what was aimed at, from point blank, and how much it drops per tile
+const BASE_HIT_CHANCE: f32 = 0.95;
+const HIT_CHANCE_FALLOFF: f32 = 0.05;
+const MIN_HIT_CHANCE: f32 = 0.3;
+// chance a shot hits someone standing in its way
+const IN_THE_WAY_HIT_CHANCE: f32 = 0.5;
+
+const PLAYER: usize = 0;
+
+/// something that shoots. `ammo` counts down per shot; a weapon with no `max_ammo`
+/// never runs dry (monsters don't carry spare magazines)
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RangedWeapon {
+    pub power: i32,
+    pub range: i32,
+    #[serde(default)]
+    pub ammo: i32,
+    pub max_ammo: Option<i32>,
+}
+
+/// the inventory index of the player's equipped ranged weapon, if any
+pub fn equipped_ranged_weapon(game: &Game) -> Option<usize> {
+    game.inventory.iter().position(|item| {
+        item.ranged.is_some() && item.equipment.is_some_and(|e| e.equipped)
+    })
+}
+
+/// chance of hitting what was aimed at this far away
+fn hit_chance(distance: f32) -> f32 {
+    (BASE_HIT_CHANCE - HIT_CHANCE_FALLOFF * distance).max(MIN_HIT_CHANCE)
+}
+
+/// the player fires their equipped weapon at `target`. Returns whether that took a turn
+pub fn player_fire(target: (i32, i32), game: &mut Game, objects: &mut [Object]) -> bool {
+    let weapon_id = match equipped_ranged_weapon(game) {
+        Some(weapon_id) => weapon_id,
+        None => {
+            game.messages.add("You have nothing to shoot with.", WHITE);
+            return false;
+        }
+    };
+    let weapon = game.inventory[weapon_id].ranged.unwrap();
+    let (player_x, player_y) = objects[PLAYER].pos();
+    let distance = (((target.0 - player_x).pow(2) + (target.1 - player_y).pow(2)) as f32).sqrt();
+    if target == (player_x, player_y) || !game.fov.is_in_fov(target.0, target.1) {
+        game.messages.add("You can't aim there.", WHITE);
+        return false;
+    }
+    if distance > weapon.range as f32 {
+        game.messages.add("That's out of range.", WHITE);
+        return false;
+    }
+    if weapon.max_ammo.is_some() && weapon.ammo <= 0 {
+        game.messages.add(
+            format!("*click* The {} is out of ammo.", game.inventory[weapon_id].name.to_lowercase()),
+            RED,
+        );
+        return false;
+    }
+
+    if let Some(ref mut weapon) = game.inventory[weapon_id].ranged {
+        weapon.ammo -= 1;
+    }
+    fire(PLAYER, target, weapon, "shoot", game, objects);
+    true
+}
+
+/// shoot from `shooter_id` towards `target`. The shot flies in a straight line until it
+/// hits a wall, runs out of range or hits someone: whoever was aimed at is hit with a
+/// chance that drops with distance, anyone else in the way may catch a stray shot
+pub fn fire(
+    shooter_id: usize,
+    target: (i32, i32),
+    weapon: RangedWeapon,
+    verb: &str,
+    game: &mut Game,
+    objects: &mut [Object],
+) {
+    let origin = objects[shooter_id].pos();
+    for (x, y) in ray(origin, target, weapon.range, &game.map) {
+        if game.map[x as usize][y as usize].block_sight {
+            game.messages.add("The shot hits the wall.", LIGHT_GREY);
+            return;
+        }
+
+        let victim_id = objects.iter().position(|object| {
+            object.pos() == (x, y) && object.blocks && object.fighter.is_some()
+        });
+        if let Some(victim_id) = victim_id {
+            let chance = if (x, y) == target {
+                hit_chance(objects[shooter_id].distance_to(&objects[victim_id]))
+            } else {
+                IN_THE_WAY_HIT_CHANCE
+            };
+            if game.rng.gen::<f32>() < chance {
+                let (shooter, victim) = mut_two(shooter_id, victim_id, objects);
+                shooter.strike(victim, weapon.power, verb, game);
+                return;
+            }
+            game.messages.add(
+                format!("The shot misses {}.", objects[victim_id].name.to_lowercase()),
+                LIGHT_GREY,
+            );
+        }
+    }
+    game.messages.add("The shot flies off into the dark.", LIGHT_GREY);
+}
@@ -0,0 +1,41 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// The game-wide random number generator (xorshift64*). Map generation, monster
+/// placement and combat all draw from this one, so a seed reproduces the whole game.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameRng {
+    state: u64,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        // run the seed through splitmix64 so small seeds like 1, 2, 3 still
+        // start from well mixed (and never zero) states
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        GameRng {
+            state: if z == 0 { 1 } else { z },
+        }
+    }
+
+    /// pick a seed for a game started without `--seed`. Kept short so it's easy to read out
+    pub fn random_seed() -> u64 {
+        u64::from(rand::random::<u32>())
+    }
+}
+
+impl Rng for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
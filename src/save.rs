@@ -0,0 +1,70 @@
+use crate::fov::FovMap;
+use crate::game::*;
+use crate::maps::*;
+use crate::object::Object;
+use crate::structs::*;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// where the frontend keeps its save, relative to the working directory
+pub const SAVE_PATH: &str = "savegame";
+
+/// bump this whenever the saved state changes shape, old saves are refused rather
+/// than loaded half-right
+pub const SAVE_VERSION: u32 = 19;
+
+#[derive(Serialize)]
+struct SaveRef<'a> {
+    version: u32,
+    game: &'a Game,
+    objects: &'a [Object],
+}
+
+#[derive(Deserialize)]
+struct SaveFile {
+    game: Game,
+    objects: Vec<Object>,
+}
+
+/// write the whole game state (all levels, objects, messages and RNG) to `path`
+pub fn save_game(path: &Path, game: &Game, objects: &[Object]) -> Result<(), Box<dyn Error>> {
+    let save = SaveRef {
+        version: SAVE_VERSION,
+        game,
+        objects,
+    };
+    fs::write(path, serde_json::to_string(&save)?)?;
+    Ok(())
+}
+
+/// read a game saved by `save_game` back, with the FOV rebuilt around the player
+pub fn load_game(path: &Path) -> Result<(Game, Vec<Object>), Box<dyn Error>> {
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+
+    // check the version before anything else, a different version may not even parse
+    let version = json.get("version").and_then(|v| v.as_u64());
+    if version != Some(u64::from(SAVE_VERSION)) {
+        return Err(format!(
+            "the save file is from another version of the game ({}, expected {})",
+            version.map_or("unknown".into(), |v| v.to_string()),
+            SAVE_VERSION
+        )
+        .into());
+    }
+
+    let SaveFile { mut game, objects } = serde_json::from_value(json)?;
+    game.fov = FovMap::new(MAP_WIDTH, MAP_HEIGHT);
+    initialise_fov(&mut game);
+    compute_fov(&mut game, &objects);
+    Ok((game, objects))
+}
+
+/// remove the save, e.g. once its player has died. A missing save is not an error
+pub fn delete_save(path: &Path) -> Result<(), Box<dyn Error>> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
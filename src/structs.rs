@@ -1,20 +1,48 @@
-use tcod::map::{Map as FovMap};
-use tcod::console::*;
-use tcod::colors::*;
+use crate::colors::Color;
+use crate::fov::FovMap;
 use crate::maps::*;
+use crate::object::Object;
+use crate::rng::GameRng;
+use crate::templates::Templates;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-pub struct Tcod {
-    pub root: Root,
-    pub con: Offscreen,
-    pub panel: Offscreen,
-    pub fov: FovMap
+/// a dungeon level the player isn't on, kept around so it can be revisited
+#[derive(Serialize, Deserialize)]
+pub struct Level {
+    pub map: Map,
+    pub objects: Vec<Object>,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Game {
     pub map: Map,
-    pub messages: Messages
+    pub messages: Messages,
+    /// not saved, it's rebuilt from the map on load
+    #[serde(skip)]
+    pub fov: FovMap,
+    pub rng: GameRng,
+    /// the seed the game was started from, so it can be reported and replayed
+    pub seed: u64,
+    /// how many turns have passed since the game started
+    pub turn: u32,
+    /// the dungeon level the player is on, starting at 1
+    pub level: u32,
+    /// every other level visited so far, by level number
+    pub levels: BTreeMap<u32, Level>,
+    /// how many monsters the player has killed
+    pub kills: u32,
+    /// what killed the player, once something has
+    pub cause_of_death: Option<String>,
+    /// how much air is left in the player's tanks, see `survival::breathe`
+    pub oxygen: i32,
+    /// the items the player carries, at most `MAX_INVENTORY` of them
+    pub inventory: Vec<Object>,
+    /// the monsters and items new levels are filled with
+    pub templates: Templates,
 }
 
+#[derive(Default, Serialize, Deserialize)]
 pub struct Messages {
     pub messages: Vec<(String, Color)>,
 }
@@ -33,4 +61,4 @@ impl Messages {
     pub fn iter(&self) -> impl DoubleEndedIterator<Item = &(String, Color)> {
         self.messages.iter()
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,110 @@
+use crate::colors::*;
+use crate::atmosphere::is_pressurized;
+use crate::effects::*;
+use crate::object::*;
+use crate::structs::*;
+use crate::tile::TileKind;
+use serde::{Deserialize, Serialize};
+
+/// how much air the player's tanks hold, in turns of breathing
+pub const MAX_OXYGEN: i32 = 300;
+/// how much a canister puts back in the tanks
+pub const CANISTER_OXYGEN: i32 = 100;
+// how much oxygen breathing takes each turn
+const BREATH: i32 = 1;
+// extra oxygen the suit goes through each turn in vacuum, keeping its pressure up
+const VACUUM_DRAIN: i32 = 2;
+// extra oxygen lost each turn through a wrecked suit, or with no suit at all
+const MAX_LEAK: i32 = 3;
+// the player gets warned once the tanks drop below this
+const LOW_OXYGEN: i32 = MAX_OXYGEN / 4;
+const SUFFOCATION_DAMAGE: i32 = 2;
+
+const PLAYER: usize = 0;
+
+/// how much punishment a hardsuit can still take before its seals give out
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Integrity {
+    pub current: i32,
+    pub max: i32,
+}
+
+/// the inventory index of the hardsuit the player is wearing, if any
+pub fn worn_suit(game: &Game) -> Option<usize> {
+    game.inventory.iter().position(|item| {
+        item.integrity.is_some() && item.equipment.is_some_and(|e| e.equipped)
+    })
+}
+
+/// how much oxygen leaks out each turn, on top of breathing. An intact suit holds
+/// everything, a wrecked one (or none) leaks `MAX_LEAK`
+pub fn oxygen_leak(game: &Game) -> i32 {
+    match worn_suit(game).and_then(|id| game.inventory[id].integrity) {
+        Some(integrity) => (integrity.max - integrity.current) * MAX_LEAK / integrity.max,
+        None => MAX_LEAK,
+    }
+}
+
+/// wear down the player's hardsuit after a hit for `damage`
+pub fn damage_suit(damage: i32, game: &mut Game) {
+    let suit_id = match worn_suit(game) {
+        Some(id) => id,
+        None => return,
+    };
+    if let Some(ref mut integrity) = game.inventory[suit_id].integrity {
+        if integrity.current == 0 || damage <= 0 {
+            return;
+        }
+        integrity.current = (integrity.current - damage).max(0);
+        if integrity.current == 0 {
+            game.messages.add("Your hardsuit's seals give out! Air hisses out of it.", ORANGE);
+        }
+    }
+}
+
+/// top the player's tanks up by `amount`, without going over the maximum
+pub fn refill_oxygen(amount: i32, game: &mut Game) {
+    game.oxygen = (game.oxygen + amount).min(MAX_OXYGEN);
+}
+
+/// everyone breathes for a turn. Monsters that need air start suffocating in
+/// vacuum, the player goes through their tanks, see `player_breathes`
+pub fn breathe(game: &mut Game, objects: &mut [Object]) {
+    for object in objects.iter_mut() {
+        if object.needs_air && object.alive && !is_pressurized(&game.map, object.x, object.y) {
+            let suffocating = StatusEffect::new(EffectKind::Suffocating, 2, SUFFOCATION_DAMAGE);
+            apply_effect(object, suffocating, game);
+        }
+    }
+    player_breathes(game, objects);
+}
+
+/// the player breathes from their tanks every turn. They drain faster through a damaged
+/// suit and faster still in vacuum, and running dry starts the suffocation. Stations refill them
+fn player_breathes(game: &mut Game, objects: &mut [Object]) {
+    if !objects[PLAYER].alive {
+        return;
+    }
+    let (x, y) = objects[PLAYER].pos();
+    if game.map[x as usize][y as usize].kind == TileKind::OxygenStation {
+        if game.oxygen < MAX_OXYGEN {
+            game.messages.add("You hook up to the station and refill your tanks.", SKY);
+            refill_oxygen(MAX_OXYGEN, game);
+        }
+        return;
+    }
+
+    let mut drain = BREATH + oxygen_leak(game);
+    if !is_pressurized(&game.map, x, y) {
+        drain += VACUUM_DRAIN;
+    }
+    let before = game.oxygen;
+    game.oxygen = (game.oxygen - drain).max(0);
+    if before >= LOW_OXYGEN && game.oxygen < LOW_OXYGEN {
+        game.messages.add("Your oxygen is running low!", LIGHT_YELLOW);
+    }
+    if game.oxygen == 0 {
+        let suffocating = StatusEffect::new(EffectKind::Suffocating, 2, SUFFOCATION_DAMAGE);
+        apply_effect(&mut objects[PLAYER], suffocating, game);
+    }
+}
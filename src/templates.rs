@@ -0,0 +1,168 @@
+use crate::colors::Color;
+use crate::effects::StatusEffect;
+use crate::items::{Equipment, Item};
+use crate::maps::{from_dungeon_level, Transition};
+use crate::object::*;
+use crate::prefabs::Prefab;
+use crate::ranged::RangedWeapon;
+use crate::survival::Integrity;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// where the frontend looks for the monster and item templates, relative to the working directory
+pub const TEMPLATES_PATH: &str = "data/templates.json";
+
+// the shipped templates, built in for when the data file can't be found
+const BUILTIN_TEMPLATES: &str = include_str!("../data/templates.json");
+
+/// which `Ai` a monster starts out with
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AiKind {
+    Basic,
+    Stationary,
+    Ranged,
+}
+
+/// a kind of monster, as described in the templates file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MonsterTemplate {
+    pub name: String,
+    pub glyph: char,
+    pub color: Color,
+    pub hp: i32,
+    pub defense: i32,
+    pub power: i32,
+    pub xp: i32,
+    pub attack_message: String,
+    pub ai: AiKind,
+    #[serde(default)]
+    pub ranged: Option<RangedWeapon>,
+    #[serde(default)]
+    pub inflicts: Option<StatusEffect>,
+    #[serde(default)]
+    pub needs_air: bool,
+    /// how likely it is to be picked, relative to the others, from each level down
+    pub weight: Vec<Transition>,
+    /// the first dungeon level it shows up on
+    pub min_depth: u32,
+}
+
+impl MonsterTemplate {
+    /// a fresh monster of this kind, standing at (x, y)
+    pub fn spawn(&self, x: i32, y: i32) -> Object {
+        let mut monster = Object::new(x, y, self.glyph, &self.name, self.color, true);
+        monster.fighter = Some(Fighter {
+            base_max_hp: self.hp,
+            hp: self.hp,
+            base_defense: self.defense,
+            base_power: self.power,
+            xp: self.xp,
+            on_death: DeathCallback::Monster,
+        });
+        monster.attack_message = self.attack_message.clone();
+        monster.ai = Some(match self.ai {
+            AiKind::Basic => Ai::basic(),
+            AiKind::Stationary => Ai::Stationary,
+            AiKind::Ranged => Ai::ranged(),
+        });
+        monster.ranged = self.ranged;
+        monster.inflicts = self.inflicts;
+        monster.needs_air = self.needs_air;
+        monster.alive = true;
+        monster
+    }
+}
+
+/// a kind of item, as described in the templates file. What it does when used is up
+/// to its `item` kind, the rest is all here
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ItemTemplate {
+    pub item: Item,
+    pub name: String,
+    pub glyph: char,
+    pub color: Color,
+    #[serde(default)]
+    pub equipment: Option<Equipment>,
+    #[serde(default)]
+    pub ranged: Option<RangedWeapon>,
+    /// how much punishment it can take, for hardsuits
+    #[serde(default)]
+    pub integrity: Option<i32>,
+    /// how likely it is to be picked, relative to the others, from each level down
+    pub weight: Vec<Transition>,
+    /// the first dungeon level it shows up on
+    pub min_depth: u32,
+}
+
+impl ItemTemplate {
+    /// a fresh item of this kind, lying at (x, y)
+    pub fn spawn(&self, x: i32, y: i32) -> Object {
+        let mut object = Object::new(x, y, self.glyph, &self.name, self.color, false);
+        object.item = Some(self.item);
+        object.equipment = self.equipment;
+        object.ranged = self.ranged;
+        object.integrity = self.integrity.map(|max| Integrity { current: max, max });
+        object
+    }
+}
+
+/// everything that can be spawned in the dungeon, and the hand-drawn rooms. Kept with the game, so a saved
+/// game goes on with the templates it was started with
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Templates {
+    pub monsters: Vec<MonsterTemplate>,
+    pub items: Vec<ItemTemplate>,
+    #[serde(default)]
+    pub prefabs: Vec<Prefab>,
+}
+
+impl Templates {
+    /// read the templates from a JSON file like the one shipped in `data/`
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let templates: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+        for prefab in &templates.prefabs {
+            prefab.check()?;
+        }
+        // the game hands these out itself, so they have to be there
+        for item in [Item::Hardsuit, Item::Keycard] {
+            if templates.item(item).is_none() {
+                return Err(format!("there is no template for the {:?}", item).into());
+            }
+        }
+        Ok(templates)
+    }
+
+    /// the templates the game was built with
+    pub fn builtin() -> Self {
+        serde_json::from_str(BUILTIN_TEMPLATES).expect("the built-in templates should parse")
+    }
+
+    /// the template for a kind of item
+    pub fn item(&self, item: Item) -> Option<&ItemTemplate> {
+        self.items.iter().find(|template| template.item == item)
+    }
+
+    /// the monsters that can show up at `depth`, as indexes into `monsters` with their weights
+    pub fn monster_chances(&self, depth: u32) -> Vec<(usize, u32)> {
+        self.monsters
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.min_depth <= depth)
+            .map(|(i, m)| (i, from_dungeon_level(&m.weight, depth)))
+            .filter(|&(_, weight)| weight > 0)
+            .collect()
+    }
+
+    /// the items that can show up at `depth`, as indexes into `items` with their weights
+    pub fn item_chances(&self, depth: u32) -> Vec<(usize, u32)> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.min_depth <= depth)
+            .map(|(i, t)| (i, from_dungeon_level(&t.weight, depth)))
+            .filter(|&(_, weight)| weight > 0)
+            .collect()
+    }
+}
@@ -1,25 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+/// what a tile is, on top of whether it blocks movement and sight
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TileKind {
+    Floor,
+    Wall,
+    StairsDown,
+    StairsUp,
+    /// refills the player's oxygen while they stand on it
+    OxygenStation,
+    /// a hole in the hull, venting everything connected to it into space
+    HullBreach,
+    /// open or closed, going by whether it's `blocked`
+    Door,
+    /// a door that lets the air through while it's open, like any other, but cycles
+    /// shut on its own once nobody is using it. See `doors::cycle_airlocks`
+    Airlock,
+    /// a closed door that takes a keycard to open, after which it's a plain `Door`
+    LockedDoor,
+}
+
 /// a tile of the map and its properties
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Tile {
+    pub kind: TileKind,
     pub blocked: bool,
     pub explored: bool,
     pub block_sight: bool,
+    /// whether there's air here. See `atmosphere::vent`
+    pub pressurized: bool,
 }
 
 impl Tile {
     pub fn empty() -> Self {
         Tile {
+            kind: TileKind::Floor,
             blocked: false,
             explored: false,
             block_sight: false,
+            pressurized: true,
         }
     }
     
     pub fn wall() -> Self {
         Tile {
+            kind: TileKind::Wall,
             blocked: true,
             explored: false,
             block_sight: true,
+            pressurized: false,
+        }
+    }
+
+    pub fn stairs_down() -> Self {
+        Tile {
+            kind: TileKind::StairsDown,
+            ..Tile::empty()
+        }
+    }
+
+    pub fn stairs_up() -> Self {
+        Tile {
+            kind: TileKind::StairsUp,
+            ..Tile::empty()
+        }
+    }
+
+    pub fn oxygen_station() -> Self {
+        Tile {
+            kind: TileKind::OxygenStation,
+            ..Tile::empty()
+        }
+    }
+
+    pub fn hull_breach() -> Self {
+        Tile {
+            kind: TileKind::HullBreach,
+            pressurized: false,
+            ..Tile::empty()
+        }
+    }
+
+    /// a closed door of the given kind
+    pub fn door(kind: TileKind) -> Self {
+        Tile {
+            kind,
+            blocked: true,
+            block_sight: true,
+            ..Tile::empty()
+        }
+    }
+
+    pub fn is_door(&self) -> bool {
+        matches!(self.kind, TileKind::Door | TileKind::Airlock | TileKind::LockedDoor)
+    }
+
+    /// open or close a door. Nothing else opens
+    pub fn set_open(&mut self, open: bool) {
+        if self.is_door() {
+            self.blocked = !open;
+            self.block_sight = !open;
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,40 @@
+use aeros::ai::ai_take_turn;
+use aeros::object::{Ai, Object};
+use aeros::structs::Game;
+
+mod common;
+
+use common::*;
+
+/// hurt the monster at `id` down to a single hit point
+fn nearly_kill(id: usize, game: &mut Game, objects: &mut [Object]) {
+    let hp = objects[id].fighter.map_or(0, |f| f.hp);
+    objects[id].take_damage(hp - 1, game);
+}
+
+#[test]
+fn badly_hurt_monsters_flee() {
+    let (mut game, mut objects) = open_floor();
+    objects.push(monster_at(12, 10));
+    nearly_kill(1, &mut game, &mut objects);
+    assert!(matches!(objects[1].ai, Some(Ai::Fleeing { .. })));
+
+    let start = objects[1].pos();
+    ai_take_turn(1, &mut game, &mut objects);
+    assert_ne!(objects[1].pos(), start);
+}
+
+#[test]
+fn fleeing_wears_off_even_without_healing() {
+    let (mut game, mut objects) = open_floor();
+    objects.push(monster_at(12, 10));
+    nearly_kill(1, &mut game, &mut objects);
+    for _ in 0..20 {
+        ai_take_turn(1, &mut game, &mut objects);
+    }
+    assert!(matches!(objects[1].ai, Some(Ai::Basic { .. })));
+
+    // and having got its nerve back, it doesn't run again
+    objects[1].take_damage(0, &mut game);
+    assert!(matches!(objects[1].ai, Some(Ai::Basic { .. })));
+}
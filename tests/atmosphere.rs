@@ -0,0 +1,130 @@
+use aeros::atmosphere::*;
+use aeros::doors::cycle_airlocks;
+use aeros::maps::*;
+use aeros::structs::Game;
+use aeros::tile::*;
+
+mod common;
+
+use common::*;
+
+const PLAYER: usize = 0;
+
+/// two rooms side by side, joined by a tunnel along y = 5 with a door where it
+/// leaves the first one
+fn two_rooms() -> Map {
+    let mut map = vec![vec![Tile::wall(); 12]; 30];
+    create_room(Rect::new(1, 1, 8, 8), &mut map);
+    create_room(Rect::new(18, 1, 8, 8), &mut map);
+    create_h_tunnel(5, 22, 5, &mut map);
+    map[9][5] = Tile::door(TileKind::Door);
+    map
+}
+
+#[test]
+fn a_breach_vents_its_own_room() {
+    let mut map = two_rooms();
+    map[3][3] = Tile::hull_breach();
+    assert!(vent_breaches(&mut map));
+    assert!(!is_pressurized(&map, 5, 5));
+    assert!(!is_pressurized(&map, 7, 7));
+}
+
+#[test]
+fn a_breach_leaves_the_room_next_door_alone() {
+    let mut map = two_rooms();
+    map[3][3] = Tile::hull_breach();
+    vent_breaches(&mut map);
+    assert!(is_pressurized(&map, 22, 5));
+    assert!(is_pressurized(&map, 20, 3));
+    // the closed door keeps it out of the tunnel between them too
+    assert!(is_pressurized(&map, 13, 5));
+}
+
+#[test]
+fn opening_the_door_vents_the_tunnel_and_the_room_beyond() {
+    let mut map = two_rooms();
+    map[3][3] = Tile::hull_breach();
+    vent_breaches(&mut map);
+    map[9][5].set_open(true);
+    assert!(vent_breaches(&mut map));
+    assert!(!is_pressurized(&map, 13, 5));
+    assert!(!is_pressurized(&map, 22, 5));
+    assert!(!is_pressurized(&map, 20, 3));
+}
+
+#[test]
+fn a_breach_leaves_an_unconnected_room_alone() {
+    let mut map = vec![vec![Tile::wall(); 12]; 30];
+    create_room(Rect::new(1, 1, 8, 8), &mut map);
+    create_room(Rect::new(18, 1, 8, 8), &mut map);
+    map[3][3] = Tile::hull_breach();
+    vent_breaches(&mut map);
+    assert!(!is_pressurized(&map, 5, 5));
+    assert!(is_pressurized(&map, 22, 5));
+}
+
+/// two rooms sharing a wall, with an airlock in it
+fn airlocked_rooms() -> Map {
+    let mut map = vec![vec![Tile::wall(); 12]; 30];
+    create_room(Rect::new(1, 1, 8, 8), &mut map);
+    create_room(Rect::new(9, 1, 8, 8), &mut map);
+    map[9][5] = Tile::door(TileKind::Airlock);
+    map[3][3] = Tile::hull_breach();
+    map
+}
+
+#[test]
+fn a_closed_airlock_holds_the_air_in() {
+    let mut map = airlocked_rooms();
+    vent_breaches(&mut map);
+    assert!(!is_pressurized(&map, 5, 5));
+    assert!(is_pressurized(&map, 13, 5));
+}
+
+#[test]
+fn an_open_airlock_lets_the_air_out() {
+    let mut map = airlocked_rooms();
+    vent_breaches(&mut map);
+    map[9][5].set_open(true);
+    assert!(vent_breaches(&mut map));
+    assert!(!is_pressurized(&map, 13, 5));
+}
+
+#[test]
+fn closing_the_airlock_brings_the_air_back() {
+    let mut map = airlocked_rooms();
+    map[9][5].set_open(true);
+    vent_breaches(&mut map);
+    map[9][5].set_open(false);
+    assert!(repressurize(&mut map));
+    assert!(is_pressurized(&map, 13, 5));
+    // the breached side stays empty
+    assert!(!is_pressurized(&map, 5, 5));
+}
+
+/// an open airlock at (15, 10) in a wall across the floor
+fn open_airlock(game: &mut Game) {
+    for y in 1..MAP_HEIGHT - 1 {
+        game.map[15][y as usize] = Tile::wall();
+    }
+    game.map[15][10] = Tile::door(TileKind::Airlock);
+    game.map[15][10].set_open(true);
+}
+
+#[test]
+fn an_airlock_nobody_is_using_cycles_shut() {
+    let (mut game, objects) = open_floor();
+    open_airlock(&mut game);
+    cycle_airlocks(&mut game, &objects);
+    assert!(game.map[15][10].blocked);
+}
+
+#[test]
+fn an_airlock_stays_open_while_someone_is_next_to_it() {
+    let (mut game, mut objects) = open_floor();
+    open_airlock(&mut game);
+    objects[PLAYER].set_pos(14, 10);
+    cycle_airlocks(&mut game, &objects);
+    assert!(!game.map[15][10].blocked);
+}
@@ -0,0 +1,93 @@
+use aeros::command::*;
+use aeros::effects::*;
+use aeros::game::*;
+use aeros::maps::find_tile;
+use aeros::templates::Templates;
+use aeros::tile::TileKind;
+
+mod common;
+
+use common::*;
+
+const PLAYER: usize = 0;
+
+#[test]
+fn moving_steps_one_tile() {
+    let (mut game, mut objects) = open_floor();
+    assert!(apply_command(Command::Move { dx: 1, dy: -1 }, &mut game, &mut objects));
+    assert_eq!(objects[PLAYER].pos(), (11, 9));
+}
+
+#[test]
+fn moves_further_than_a_step_are_refused() {
+    let (mut game, mut objects) = open_floor();
+    for (dx, dy) in [(100, 0), (2, 0), (0, -2), (0, 0), (-1000, 1000)] {
+        assert!(!apply_command(Command::Move { dx, dy }, &mut game, &mut objects));
+        assert_eq!(objects[PLAYER].pos(), (10, 10));
+    }
+}
+
+#[test]
+fn attacks_only_reach_the_next_tile() {
+    let (mut game, mut objects) = open_floor();
+    objects.push(monster_at(12, 10));
+    assert!(!apply_command(Command::Attack { dx: 2, dy: 0 }, &mut game, &mut objects));
+    assert!(!apply_command(Command::Attack { dx: 1, dy: 0 }, &mut game, &mut objects));
+
+    objects[1].set_pos(11, 10);
+    assert!(apply_command(Command::Attack { dx: 1, dy: 0 }, &mut game, &mut objects));
+    assert_eq!(objects[PLAYER].pos(), (10, 10));
+}
+
+#[test]
+fn moving_into_a_monster_attacks_it() {
+    let (mut game, mut objects) = open_floor();
+    objects.push(monster_at(11, 10));
+    let hp = objects[1].fighter.map(|f| f.hp);
+    assert!(apply_command(Command::Move { dx: 1, dy: 0 }, &mut game, &mut objects));
+    assert_eq!(objects[PLAYER].pos(), (10, 10));
+    assert!(objects[1].fighter.map(|f| f.hp) < hp || !objects[1].alive);
+}
+
+#[test]
+fn missing_inventory_slots_are_refused() {
+    let (mut game, mut objects) = open_floor();
+    let slots = game.inventory.len();
+    assert!(!apply_command(Command::UseItem(slots), &mut game, &mut objects));
+    assert!(!apply_command(Command::DropItem(slots + 10), &mut game, &mut objects));
+    assert_eq!(game.inventory.len(), slots);
+}
+
+#[test]
+fn stairs_only_go_the_way_they_lead() {
+    let (mut game, mut objects) = new_game(7, Templates::builtin());
+    assert!(!apply_command(Command::Descend, &mut game, &mut objects));
+    let (x, y) = find_tile(&game.map, TileKind::StairsDown).unwrap();
+    objects[PLAYER].set_pos(x, y);
+    assert!(!apply_command(Command::Ascend, &mut game, &mut objects));
+    assert!(apply_command(Command::Descend, &mut game, &mut objects));
+    assert_eq!(game.level, 2);
+    assert!(apply_command(Command::Ascend, &mut game, &mut objects));
+    assert_eq!(game.level, 1);
+}
+
+#[test]
+fn a_stunned_player_loses_the_turn() {
+    let (mut game, mut objects) = open_floor();
+    apply_effect(&mut objects[PLAYER], StatusEffect::new(EffectKind::Stunned, 2, 0), &mut game);
+    assert!(apply_command(Command::Move { dx: 1, dy: 0 }, &mut game, &mut objects));
+    assert_eq!(objects[PLAYER].pos(), (10, 10));
+}
+
+#[test]
+fn the_dead_cannot_act() {
+    let (mut game, mut objects) = open_floor();
+    let hp = objects[PLAYER].fighter.map_or(0, |f| f.hp);
+    objects[PLAYER].take_damage(hp, &mut game);
+    assert!(!objects[PLAYER].alive);
+
+    for command in [Command::Move { dx: 1, dy: 0 }, Command::Wait, Command::PickUp] {
+        assert!(!apply_command(command, &mut game, &mut objects));
+    }
+    assert_eq!(objects[PLAYER].pos(), (10, 10));
+}
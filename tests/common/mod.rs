@@ -0,0 +1,28 @@
+use aeros::game::new_game;
+use aeros::maps::{MAP_HEIGHT, MAP_WIDTH};
+use aeros::object::Object;
+use aeros::structs::Game;
+use aeros::templates::Templates;
+use aeros::tile::Tile;
+
+const PLAYER: usize = 0;
+
+/// a fresh game with the player alone in the middle of an open floor
+pub fn open_floor() -> (Game, Vec<Object>) {
+    let (mut game, mut objects) = new_game(1, Templates::builtin());
+    objects.truncate(PLAYER + 1);
+    for x in 0..MAP_WIDTH {
+        for y in 0..MAP_HEIGHT {
+            let edge = x == 0 || y == 0 || x == MAP_WIDTH - 1 || y == MAP_HEIGHT - 1;
+            game.map[x as usize][y as usize] = if edge { Tile::wall() } else { Tile::empty() };
+        }
+    }
+    objects[PLAYER].set_pos(10, 10);
+    (game, objects)
+}
+
+/// a fish, the weakest thing on the station
+#[allow(dead_code)] // not every test file has monsters in it
+pub fn monster_at(x: i32, y: i32) -> Object {
+    Templates::builtin().monsters[0].spawn(x, y)
+}
@@ -0,0 +1,66 @@
+use aeros::effects::*;
+use aeros::game::{compute_fov, initialise_fov};
+use aeros::items::{use_item, Item};
+use aeros::templates::Templates;
+
+mod common;
+
+use common::*;
+
+const PLAYER: usize = 0;
+
+#[test]
+fn the_player_gets_the_credit_for_what_their_effects_kill() {
+    let (mut game, mut objects) = open_floor();
+    objects.push(monster_at(20, 20));
+    let xp = objects[1].fighter.map_or(0, |f| f.xp);
+    let poison = StatusEffect {
+        by_player: true,
+        ..StatusEffect::new(EffectKind::Poisoned, 5, 1000)
+    };
+    apply_effect(&mut objects[1], poison, &mut game);
+    tick_effects(&mut game, &mut objects);
+
+    assert!(!objects[1].alive);
+    assert_eq!(game.kills, 1);
+    assert_eq!(objects[PLAYER].fighter.map_or(0, |f| f.xp), xp);
+}
+
+#[test]
+fn nobody_gets_the_credit_for_what_the_station_kills() {
+    let (mut game, mut objects) = open_floor();
+    objects.push(monster_at(20, 20));
+    apply_effect(&mut objects[1], StatusEffect::new(EffectKind::Suffocating, 5, 1000), &mut game);
+    tick_effects(&mut game, &mut objects);
+
+    assert!(!objects[1].alive);
+    assert_eq!(game.kills, 0);
+    assert_eq!(objects[PLAYER].fighter.map_or(0, |f| f.xp), 0);
+}
+
+#[test]
+fn flashbangs_stun_what_they_go_off_in_front_of() {
+    let (mut game, mut objects) = open_floor();
+    objects.push(monster_at(12, 10));
+    initialise_fov(&mut game);
+    compute_fov(&mut game, &objects);
+    let flashbang = game.templates.item(Item::Flashbang).expect("no flashbang template").spawn(0, 0);
+    game.inventory.push(flashbang);
+    let flashbang = game.inventory.len() - 1;
+    assert!(use_item(flashbang, &mut game, &mut objects));
+
+    assert!(has_effect(&objects[1], EffectKind::Stunned));
+    // the player threw it, so whatever happens to the monster now is on them
+    assert!(objects[1].effects.iter().all(|effect| effect.by_player));
+}
+
+#[test]
+fn welders_set_what_they_hit_on_fire() {
+    let (mut game, mut objects) = open_floor();
+    let templates = Templates::builtin();
+    let welder = templates.monsters.iter().find(|monster| monster.name == "Welder");
+    let mut welder = welder.map(|template| template.spawn(11, 10)).expect("no welder template");
+    welder.strike(&mut objects[PLAYER], 10, "torches", &mut game);
+
+    assert!(has_effect(&objects[PLAYER], EffectKind::Burning));
+}
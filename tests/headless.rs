@@ -0,0 +1,69 @@
+use aeros::command::*;
+use aeros::game::*;
+use aeros::object::Object;
+use aeros::rng::GameRng;
+use aeros::structs::Game;
+use aeros::templates::Templates;
+use aeros::tile::TileKind;
+use rand::Rng;
+
+const PLAYER: usize = 0;
+const TURNS: u32 = 2000;
+
+/// a bot that bumps around at random, picks things up, uses them now and then and
+/// takes every way down it finds
+fn next_command(game: &Game, objects: &[Object], bot: &mut GameRng) -> Command {
+    let (x, y) = objects[PLAYER].pos();
+    if game.map[x as usize][y as usize].kind == TileKind::StairsDown {
+        return Command::Descend;
+    }
+    match bot.gen_range(0, 20) {
+        0 => Command::Wait,
+        1 => Command::PickUp,
+        2 if !game.inventory.is_empty() => Command::UseItem(bot.gen_range(0, game.inventory.len())),
+        _ => loop {
+            let (dx, dy) = (bot.gen_range(-1, 2), bot.gen_range(-1, 2));
+            if (dx, dy) != (0, 0) {
+                break Command::Move { dx, dy };
+            }
+        },
+    }
+}
+
+/// play `TURNS` turns of the game started from `seed`, or until the player dies
+fn play(seed: u64) -> (Game, Vec<Object>) {
+    let (mut game, mut objects) = new_game(seed, Templates::builtin());
+    let mut bot = GameRng::new(seed);
+    for _ in 0..TURNS {
+        if !objects[PLAYER].alive {
+            break;
+        }
+        let command = next_command(&game, &objects, &mut bot);
+        if apply_command(command, &mut game, &mut objects) {
+            monsters_take_turn(&mut game, &mut objects);
+        }
+        while objects[PLAYER].alive && can_level_up(&objects) {
+            level_up(LevelUpChoice::Constitution, &mut game, &mut objects);
+        }
+    }
+    (game, objects)
+}
+
+#[test]
+fn games_run_without_a_display() {
+    for seed in 0..20 {
+        let (game, objects) = play(seed);
+        assert!(game.turn > 0);
+        assert!(!objects[PLAYER].alive || game.turn >= TURNS / 2);
+    }
+}
+
+#[test]
+fn the_same_seed_and_commands_play_out_the_same() {
+    let (first_game, first_objects) = play(42);
+    let (second_game, second_objects) = play(42);
+    assert_eq!(
+        serde_json::to_string(&(&first_game, &first_objects)).unwrap(),
+        serde_json::to_string(&(&second_game, &second_objects)).unwrap()
+    );
+}
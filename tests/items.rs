@@ -0,0 +1,15 @@
+use aeros::game::new_game;
+use aeros::items::Item;
+use aeros::templates::Templates;
+
+#[test]
+fn the_starting_hardsuit_comes_from_its_template() {
+    let (game, _) = new_game(1, Templates::builtin());
+    let template = game.templates.item(Item::Hardsuit).expect("no hardsuit template");
+    let hardsuit = &game.inventory[0];
+
+    assert_eq!(hardsuit.name, template.name);
+    assert_eq!(hardsuit.char, template.glyph);
+    assert_eq!(hardsuit.integrity.map(|integrity| integrity.max), template.integrity);
+    assert!(hardsuit.equipment.is_some_and(|equipment| equipment.equipped));
+}
@@ -0,0 +1,26 @@
+use aeros::prefabs::Prefab;
+use aeros::templates::Templates;
+use std::fs;
+
+#[test]
+fn the_builtin_prefabs_only_use_known_markers() {
+    for prefab in Templates::builtin().prefabs {
+        assert_eq!(prefab.check(), Ok(()));
+    }
+}
+
+#[test]
+fn loading_templates_with_an_unknown_marker_fails() {
+    let mut templates = Templates::builtin();
+    templates.prefabs.push(Prefab {
+        name: "Typo".into(),
+        rows: vec!["###".into(), "#X+".into(), "###".into()],
+    });
+    let path = std::env::temp_dir().join(format!("aeros-prefab-typo-{}.json", std::process::id()));
+    fs::write(&path, serde_json::to_string(&templates).unwrap()).unwrap();
+    let loaded = Templates::load(&path);
+    fs::remove_file(&path).unwrap();
+
+    let error = loaded.expect_err("the typo should be turned away").to_string();
+    assert!(error.contains("'X'"), "{}", error);
+}
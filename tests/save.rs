@@ -0,0 +1,61 @@
+use aeros::command::*;
+use aeros::game::*;
+use aeros::maps::{MAP_HEIGHT, MAP_WIDTH};
+use aeros::save::*;
+use aeros::templates::Templates;
+use std::fs;
+use std::path::PathBuf;
+
+/// somewhere to save to that no other test uses
+fn save_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("aeros-{}-{}.json", name, std::process::id()))
+}
+
+#[test]
+fn a_saved_game_loads_back_the_same() {
+    let (mut game, mut objects) = new_game(7, Templates::builtin());
+    for (dx, dy) in [(1, 0), (0, 1), (-1, 0), (0, -1)] {
+        if apply_command(Command::Move { dx, dy }, &mut game, &mut objects) {
+            monsters_take_turn(&mut game, &mut objects);
+        }
+    }
+    // with a level behind it too
+    change_level(&mut game, &mut objects, 2);
+
+    let path = save_path("save");
+    save_game(&path, &game, &objects).unwrap();
+    let loaded = load_game(&path);
+    fs::remove_file(&path).unwrap();
+    let (loaded_game, loaded_objects) = loaded.unwrap();
+
+    assert_eq!(
+        serde_json::to_string(&(&loaded_game, &loaded_objects)).unwrap(),
+        serde_json::to_string(&(&game, &objects)).unwrap()
+    );
+    // the FOV isn't saved, it has to come back the same from the map
+    for x in 0..MAP_WIDTH {
+        for y in 0..MAP_HEIGHT {
+            assert_eq!(loaded_game.fov.is_in_fov(x, y), game.fov.is_in_fov(x, y));
+            assert_eq!(loaded_game.fov.is_walkable(x, y), game.fov.is_walkable(x, y));
+            assert_eq!(loaded_game.fov.is_transparent(x, y), game.fov.is_transparent(x, y));
+        }
+    }
+}
+
+#[test]
+fn a_save_from_another_version_is_refused() {
+    let (game, objects) = new_game(7, Templates::builtin());
+    let path = save_path("old-save");
+    save_game(&path, &game, &objects).unwrap();
+    let json = fs::read_to_string(&path).unwrap();
+    let old = json.replacen(
+        &format!("\"version\":{}", SAVE_VERSION),
+        &format!("\"version\":{}", SAVE_VERSION - 1),
+        1,
+    );
+    fs::write(&path, old).unwrap();
+    let loaded = load_game(&path);
+    fs::remove_file(&path).unwrap();
+
+    assert!(loaded.is_err());
+}
@@ -0,0 +1,24 @@
+use aeros::survival::*;
+
+mod common;
+
+use common::*;
+
+#[test]
+fn the_tanks_drain_even_where_there_is_air() {
+    let (mut game, mut objects) = open_floor();
+    breathe(&mut game, &mut objects);
+    assert!(game.oxygen < MAX_OXYGEN);
+}
+
+#[test]
+fn the_tanks_drain_faster_in_vacuum() {
+    let (mut game, mut objects) = open_floor();
+    breathe(&mut game, &mut objects);
+    let in_air = MAX_OXYGEN - game.oxygen;
+
+    game.oxygen = MAX_OXYGEN;
+    game.map[10][10].pressurized = false;
+    breathe(&mut game, &mut objects);
+    assert!(MAX_OXYGEN - game.oxygen > in_air);
+}
@@ -0,0 +1,43 @@
+use aeros::doors::has_keycard;
+use aeros::items::Item;
+use aeros::maps::MAP_HEIGHT;
+use aeros::pathfinding::travel_path;
+use aeros::structs::Game;
+use aeros::tile::{Tile, TileKind};
+
+mod common;
+
+use common::*;
+
+/// split the floor with a wall at x = 15, with a locked door in it level with the player,
+/// and let the player know the whole map
+fn locked_off(game: &mut Game) {
+    for column in game.map.iter_mut() {
+        for tile in column.iter_mut() {
+            tile.explored = true;
+        }
+    }
+    for y in 1..MAP_HEIGHT - 1 {
+        game.map[15][y as usize] = Tile::wall();
+    }
+    game.map[15][10] = Tile::door(TileKind::LockedDoor);
+    game.map[15][10].explored = true;
+}
+
+#[test]
+fn travel_goes_around_locked_doors_without_a_keycard() {
+    let (mut game, _) = open_floor();
+    locked_off(&mut game);
+    assert!(!has_keycard(&game));
+    assert_eq!(travel_path((10, 10), (20, 10), &game.map, has_keycard(&game)), None);
+}
+
+#[test]
+fn travel_goes_through_locked_doors_with_a_keycard() {
+    let (mut game, _) = open_floor();
+    locked_off(&mut game);
+    let keycard = game.templates.item(Item::Keycard).expect("no keycard template").spawn(0, 0);
+    game.inventory.push(keycard);
+    let path = travel_path((10, 10), (20, 10), &game.map, has_keycard(&game));
+    assert!(path.is_some_and(|path| path.contains(&(15, 10))));
+}